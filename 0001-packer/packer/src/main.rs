@@ -4,7 +4,7 @@ use byteorder::{LittleEndian, WriteBytesExt};
 extern crate clap;
 use clap::{Arg, App};
 
-use crc::{crc32, Hasher32};
+use crc::crc32;
 
 use std::fs;
 use std::fs::File;
@@ -13,6 +13,7 @@ use std::process;
 use std::string::String;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Entry {
 	basepath:String,
 	filename:String,
@@ -21,7 +22,7 @@ struct Entry {
 }
 
 impl Entry {
-	fn create(basepath:&String, filename:&String) -> Entry {
+	fn create(basepath:&str, filename:&str) -> Entry {
 
 		let fullfilename = format!( "{}/{}", basepath, filename );
 
@@ -51,21 +52,22 @@ impl Entry {
 		Entry {
 			basepath: basepath.to_string(),
 			filename: filename.to_string(),
-			crc: crc,
-			size: size,
+			crc,
+			size,
 		}
 	}
 
+	#[allow(dead_code)]
 	fn display(&self) {
 		println!("Displaying Entry for filename {:?}", self.filename );
-		print!("{:?}\n", self);
+		println!("{:?}", self);
 	}
 }
 
 fn packer(
-		basepath:&String,
-		paklist:&String,
-		output:&String,
+		basepath:&str,
+		paklist:&str,
+		output:&str,
 ) -> Result<u32,&'static str> {
 
 	// iterate over paklist to get list of files needed
@@ -85,7 +87,7 @@ fn packer(
 		let filename = line.unwrap();
 		println!("{:?}", filename );
 		let entry = Entry::create(
-			&basepath,
+			basepath,
 			&filename,
 		);
 
@@ -113,12 +115,14 @@ fn packer(
 	}
 	let number_of_files: u32 = files.len() as u32;
 
-	output_file.write_all(&[
+	if output_file.write_all(&[
 		0x4f, 0x4d, 0x41, 0x52, 	// magic header
 		2,							// version
 		flags,						// flags
 		0, 0,						// reserved
-	]);
+	]).is_err() {
+		return Err("Error writing file");
+	}
 	output_file.write_u32::<LittleEndian>( number_of_files ).unwrap();
 
 	// write the directory
@@ -145,8 +149,12 @@ fn packer(
 			Err( _e ) => return Err("Error reading data file"),
 		};
 		let mut buffer = Vec::<u8>::new();
-		data_file.read_to_end(&mut buffer);
-		output_file.write_all( &buffer );
+		if data_file.read_to_end(&mut buffer).is_err() {
+			return Err("Error reading data file");
+		}
+		if output_file.write_all( &buffer ).is_err() {
+			return Err("Error writing file");
+		}
 	}
 
 	// :TODO:
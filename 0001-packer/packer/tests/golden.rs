@@ -0,0 +1,62 @@
+use std::convert::TryInto;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn testcase_path( name: &str ) -> PathBuf {
+	Path::new( env!("CARGO_MANIFEST_DIR") ).join( ".." ).join( name )
+}
+
+fn pack( basepath: &str, paklist: &str, name: &str ) -> Vec<u8> {
+	let output = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( name );
+	let _ = fs::remove_file( &output );
+
+	let status = Command::new( env!("CARGO_BIN_EXE_packer") )
+					.arg( "--basepath" ).arg( testcase_path( basepath ) )
+					.arg( "--paklist" ).arg( testcase_path( paklist ) )
+					.arg( "--output" ).arg( &output )
+					.output()
+					.unwrap()
+					.status;
+	assert!( status.success() );
+
+	fs::read( &output ).unwrap()
+}
+
+// crc of every directory entry
+fn directory_crcs( archive: &[u8] ) -> Vec<u32> {
+	let number_of_files = u32::from_le_bytes( archive[ 8..12 ].try_into().unwrap() ) as usize;
+	( 0..number_of_files ).map( |i| {
+		let start = 12 + i * 12;
+		u32::from_le_bytes( archive[ start..start+4 ].try_into().unwrap() )
+	}).collect()
+}
+
+#[test]
+fn pack_matches_golden() {
+	let output = pack( "testdata", "testdata/testdata122334444.paklist", "test1223334444.omar" );
+	assert!( output == fs::read( testcase_path( "testdata/expected-result-test1223334444.omar" ) ).unwrap() );
+}
+
+#[test]
+fn pack_names_matches_golden() {
+	let output = pack( "testdata/names", "testdata/names.paklist", "names.omar" );
+	assert!( output == fs::read( testcase_path( "testdata/expected-result-names.omar" ) ).unwrap() );
+}
+
+// the first packer only keeps [0-9a-z_.\-%], everything else becomes a space
+#[test]
+fn crc_for_tricky_names() {
+	let output = pack( "testdata/names", "testdata/names.paklist", "names_crc.omar" );
+	assert_eq!(
+		directory_crcs( &output ),
+		vec![
+			0x64B5A093,		// UPPER.TXT -> "upper.txt"
+			0xD5C773FD,		// Sub Dir/Mixed Case.Txt -> "sub dir mixed case.txt"
+			0x61EF8C90,		// with space.txt -> "with space.txt"
+			0xC0222A8D,		// ünïcödé.txt -> " n c d .txt"
+			0xED9E5E15,		// punct!#$&'()+,;=@[]^`{}~.txt -> "punct                   .txt"
+			0xF605B59F,		// dash-under_score%percent.txt -> "dash-under_score%percent.txt"
+		]
+	);
+}
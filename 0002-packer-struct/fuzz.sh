@@ -0,0 +1,6 @@
+#!/bin/sh
+
+# needs cargo-fuzz and a nightly toolchain
+# new crashes end up in packer/fuzz/artifacts/load, add minimized cases to packer/fuzz/corpus/load
+
+cd packer && cargo +nightly fuzz run load fuzz/corpus/load "$@"
@@ -2,7 +2,7 @@
 
 #OMT_PACKER=omt-packer
 
-OMT_PACKER=packer/target/debug/packer
+OMT_PACKER=../target/debug/packer
 
 #	PACK_OUT=$(ruby tools/madpack.rb Data App/data/base.omar @Data/data.paklist 2>&1)
 
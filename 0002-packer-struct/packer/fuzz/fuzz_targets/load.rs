@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use packer::Archive;
+
+fuzz_target!(|data: &[u8]| {
+	let mut archive = Archive::create( "" );
+	let _ = archive.load_from_bytes( data );
+});
@@ -0,0 +1,318 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use log::debug;
+use omt_core::{OmError, Paths};
+
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Write};
+use std::path::{Component, Path};
+
+use crate::Entry;
+use crate::Pool;
+use crate::Progress;
+
+// magic, version, flags, reserved, number of files
+const HEADER_SIZE: u64 = 4 + 1 + 1 + 2 + 4;
+// crc, pos, size
+const DIRECTORY_ENTRY_SIZE: u64 = 4 + 4 + 4;
+
+#[derive(Debug)]
+pub struct Archive {
+	basepath: String,
+	jobs: usize,
+	pub entries: Vec<Entry>
+}
+
+impl Archive {
+	pub fn create(basepath:&str) -> Archive {
+		Archive {
+			basepath: basepath.to_string(),
+			jobs: 1,
+			entries: Vec::new(),
+		}
+	}
+	pub fn add_entry(&mut self, filename:&str) -> bool {
+		let entry = Entry::create(
+			&self.basepath,
+			filename,
+		);
+
+		self.entries.push(entry);
+		true
+	}
+
+	// number of worker threads used for reading and hashing
+	pub fn set_jobs(&mut self, jobs: usize) {
+		self.jobs = jobs.max( 1 );
+	}
+
+	// same as calling add_entry for every filename, but with the file system checks spread over the worker pool
+	pub fn add_entries(&mut self, filenames: &[String]) -> bool {
+		let basepath = &self.basepath;
+		let entries = &mut self.entries;
+		entries.reserve( filenames.len() );
+		Pool::for_each_ordered(
+			filenames,
+			self.jobs,
+			|filename| Entry::create( basepath, filename ),
+			|_i, entry| {
+				entries.push( entry );
+				Ok::<(), OmError>(())
+			}
+		).is_ok()
+	}
+
+	pub fn add_entry_from_archive(&mut self, crc: u32, pos: u32, size: u32 ) -> bool {
+		let entry = Entry::create_from_archive(
+			crc,
+			pos,
+			size,
+		);
+
+		self.entries.push(entry);
+		true
+	}
+
+	// the name alone can't tell, a symlink below basepath can still point anywhere
+	fn resolves_inside( basepath: &str, filename: &str ) -> bool {
+		match ( fs::canonicalize( basepath ), fs::canonicalize( filename ) ) {
+			( Ok( base ), Ok( file ) ) => file.starts_with( base ),
+			_ => false,
+		}
+	}
+
+	// checks all entries before anything is written, and reports all problems at once
+	pub fn validate(&self) -> Result<u32,OmError> {
+		let mut problems = Vec::new();
+		for entry in &self.entries {
+			let inside = !Path::new( &entry.filename ).is_absolute() && Path::new( &entry.filename ).components().try_fold( 0i32, |depth, c| {
+				let depth = match c {
+					Component::ParentDir => depth - 1,
+					Component::Normal( _ ) => depth + 1,
+					_ => depth,
+				};
+				if depth < 0 { None } else { Some( depth ) }
+			}).is_some();
+			if !inside {
+				problems.push( format!( "{}: outside of basepath {}", entry.filename, self.basepath ) );
+				continue;
+			}
+
+			let filename = Paths::join( &self.basepath, &entry.filename );
+			match fs::metadata( &filename ) {
+				Err( _e ) => problems.push( format!( "{}: not found", filename ) ),
+				Ok( m ) if !m.is_file() => problems.push( format!( "{}: not a regular file", filename ) ),
+				Ok( _ ) => {
+					if let Err( e ) = File::open( &filename ) {
+						problems.push( format!( "{}: not readable, {}", filename, e ) );
+					} else if !Archive::resolves_inside( &self.basepath, &filename ) {
+						problems.push( format!( "{}: symlink to outside of basepath {}", filename, self.basepath ) );
+					}
+				},
+			}
+		}
+
+		if problems.is_empty() {
+			return Ok( self.entries.len() as u32 );
+		}
+		Err( OmError::Generic( format!( "{} of {} files can not be packed:\n\t{}", problems.len(), self.entries.len(), problems.join( "\n\t" ) ) ) )
+	}
+
+	// the archive is written to a temporary file next to output first, so a failed save never leaves a broken archive behind
+	pub fn save(&mut self, output: &str) -> Result<u32,OmError> {
+		self.validate()?;
+
+		let temp_output = format!( "{}.{}.tmp", output, std::process::id() );
+		let output_file = File::create(&temp_output);
+		// :TODO: rethink error handling
+		let mut output_file = match output_file {
+			Ok( p ) => BufWriter::new( p ),
+			Err( _e ) => return Err( OmError::Generic( "Error writing file".to_string() ) ),
+		};
+
+		let result = self.write( &mut output_file );
+		drop( output_file );
+		let result = result.and_then( |n| {
+			match fs::rename( &temp_output, output ) {
+				Ok( _ ) => Ok( n ),
+				Err( _e ) => Err( OmError::Generic( "Error writing file".to_string() ) ),
+			}
+		});
+		if result.is_err() {
+			let _ = fs::remove_file( &temp_output );
+		}
+		result
+	}
+
+	fn write(&mut self, output_file: &mut BufWriter<File>) -> Result<u32,OmError> {
+		// :TODO: add error handling
+		let write_names = false;
+		let mut flags: u8 = 0;
+		if write_names {
+			flags |= 1
+		}
+		let number_of_files: u32 = self.entries.len() as u32;
+
+		if output_file.write_all(&[
+			0x4f, 0x4d, 0x41, 0x52, 	// magic header
+			2,							// version
+			flags,						// flags
+			0, 0,						// reserved
+		]).is_err() {
+			return Err( OmError::Generic( "Error writing file".to_string() ) );
+		}
+		output_file.write_u32::<LittleEndian>( number_of_files ).unwrap();
+
+		// write the directory
+		let mut pos = 0;
+		for entry in &mut self.entries {
+			entry.pos = pos;
+			// crc, pos, size all as LittleEndian u32
+			output_file.write_u32::<LittleEndian>( entry.crc ).unwrap();
+			output_file.write_u32::<LittleEndian>( pos ).unwrap();
+			output_file.write_u32::<LittleEndian>( entry.size ).unwrap();
+
+			pos += entry.size;
+		}
+
+		// write data to output
+		// reading and hashing happens on the worker pool, writing stays in directory order
+		let bytes_total = self.entries.iter().map( |e| e.size as u64 ).sum();
+		let mut progress = Progress::new( "Packing", self.entries.len(), bytes_total );
+		let mut hashes = Vec::with_capacity( self.entries.len() );
+		let result = Pool::for_each_ordered(
+			&self.entries,
+			self.jobs,
+			|entry| entry.read_data(),
+			|i, data| {
+				let ( buffer, hash ) = data?;
+				if buffer.len() != self.entries[ i ].size as usize {
+					return Err( OmError::Generic( "Data file changed while packing".to_string() ) );
+				}
+				if output_file.write_all( &buffer ).is_err() {
+					return Err( OmError::Generic( "Error writing file".to_string() ) );
+				}
+				progress.add( buffer.len() as u64 );
+				hashes.push( hash );
+				Ok(())
+			}
+		);
+		progress.finish();
+		result?;
+
+		if output_file.flush().is_err() {
+			return Err( OmError::Generic( "Error writing file".to_string() ) );
+		}
+
+		for ( entry, hash ) in self.entries.iter_mut().zip( hashes ) {
+			entry.hash = hash;
+		}
+
+		Ok(number_of_files)
+	}
+
+	// offset of the first entry's data in the archive file, entry positions are relative to this
+	pub fn data_start(&self) -> u64 {
+		HEADER_SIZE + self.entries.len() as u64 * DIRECTORY_ENTRY_SIZE
+	}
+
+	pub fn load(&mut self, filename: &str ) -> Result<u32,OmError> {
+		let data = match fs::read(filename) {
+			Ok( d ) => d,
+			Err( _e ) => return Err( OmError::Generic( "Error reading file".to_string() ) ),
+		};
+
+		self.load_from_bytes( &data )
+	}
+
+	// never trust anything read from the file, all values are checked against the actual length
+	pub fn load_from_bytes(&mut self, data: &[u8] ) -> Result<u32,OmError> {
+		if ( data.len() as u64 ) < HEADER_SIZE {
+			return Err( OmError::Generic( "Truncated header".to_string() ) );
+		}
+
+		let mut cursor = Cursor::new( data );
+
+		// read header
+		// check magic
+		let magic = [ 0x4fu8, 0x4d, 0x41, 0x52 ];
+		for m in &magic {
+			let b = cursor.read_u8().unwrap_or( 0 );
+			if b != *m {
+				return Err( OmError::Generic( "Broken magic".to_string() ) );
+			}
+		}
+
+		let v = cursor.read_u8().unwrap_or( 0 );
+		if v != 2 {
+			return Err( OmError::Generic( "Wrong version".to_string() ) );
+		}
+
+		let flags = cursor.read_u8().unwrap_or( 0 );
+		if flags != 0 {
+			return Err( OmError::Generic( ":TODO: Flags not implemented".to_string() ) );
+		}
+
+		for _reserved in 0..2 {
+			let r = cursor.read_u8().unwrap_or( 0 );
+			if r != 0 {
+				return Err( OmError::Generic( ":TODO: Reserved field not zero".to_string() ) );
+			}
+		}
+
+		let number_of_files = cursor.read_u32::<LittleEndian>().unwrap_or( 0 );
+		debug!("Reading {:?} files from archive", number_of_files );
+
+		// reject absurd counts before allocating anything
+		let data_start = HEADER_SIZE + number_of_files as u64 * DIRECTORY_ENTRY_SIZE;
+		if data_start > data.len() as u64 {
+			return Err( OmError::Generic( "Directory exceeds file size".to_string() ) );
+		}
+		let data_size = data.len() as u64 - data_start;
+
+		self.entries.reserve( number_of_files as usize );
+		for _e in 0..number_of_files {
+			let crc = cursor.read_u32::<LittleEndian>().unwrap_or( 0 );
+			let pos = cursor.read_u32::<LittleEndian>().unwrap_or( 0 );
+			let size = cursor.read_u32::<LittleEndian>().unwrap_or( 0 );
+			if pos as u64 + size as u64 > data_size {
+				return Err( OmError::Generic( "Entry exceeds file size".to_string() ) );
+			}
+			self.add_entry_from_archive(crc, pos, size);
+		}
+
+		let data = &data[ data_start as usize.. ];
+		for entry in &mut self.entries {
+			if !entry.load_from_archive( data ) {
+				return Err( OmError::Generic( "Entry exceeds file size".to_string() ) );
+			}
+		}
+
+		Ok(0)
+	}
+
+	pub fn unpack(&self, targetpath: &str ) -> Result<u32,OmError> {
+		let all = ( 0..self.entries.len() ).collect::<Vec<_>>();
+		self.unpack_entries( targetpath, &all )
+	}
+
+	// same as unpack, but only for the entries with the given indices
+	pub fn unpack_entries(&self, targetpath: &str, indices: &[usize] ) -> Result<u32,OmError> {
+		for entry in indices.iter().filter_map( |i| self.entries.get( *i ) ) {
+			let filename = format!( "{}/{:#10X}", targetpath, entry.crc );
+			debug!("{:?}", filename );
+
+			let output_file = File::create(filename);
+			// :TODO: rethink error handling
+			let mut output_file = match output_file {
+				Ok( p ) => p,
+				Err( _e ) => return Err( OmError::Generic( "Error writing file".to_string() ) ),
+			};
+
+			if output_file.write_all( &entry.data ).is_err() {
+				return Err( OmError::Generic( "Error writing file".to_string() ) );
+			}
+		}
+		Ok( indices.len() as u32 )
+	}
+}
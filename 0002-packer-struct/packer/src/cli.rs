@@ -0,0 +1,364 @@
+use clap::{Arg, App, ArgMatches, SubCommand};
+use log::{debug, error, info};
+use omt_core::OmError;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+
+use crate::{Archive, Helper, Manifest, Pool, Selector, Stats};
+
+fn packer(
+		basepath:&str,
+		paklist:&str,
+		output:&str,
+		jobs:usize,
+		manifest:Option<&str>,
+) -> Result<u32,OmError> {
+	let mut archive = Archive::create(basepath);
+	archive.set_jobs( jobs );
+
+	let filenames = match Helper::filenames_in_file(paklist) {
+		Ok( filenames ) => filenames,
+		Err( _e ) => return Err( OmError::Generic( format!( "Error reading paklist {}", paklist ) ) ),
+	};
+	for filename in &filenames {
+		debug!("{:?}", filename );
+	}
+	archive.add_entries( &filenames );
+
+	let number_of_files = archive.save( output )?;
+
+	if let Some( manifest ) = manifest {
+		Manifest::create( output, &archive ).save( manifest )?;
+	}
+
+	Ok( number_of_files )
+}
+
+fn unpacker(
+		input:&str,
+		targetpath:&str,
+		only:&[&str],
+		paklist:Option<&str>,
+) -> Result<u32,OmError> {
+
+	let metadata = match fs::metadata(targetpath) {
+		Err( _err ) => return Err( OmError::Generic( "Targetpath not found".to_string() ) ), // :TODO: implement
+		Ok( md ) => md,
+	};
+
+	if !metadata.is_dir() {
+		return Err( OmError::Generic( "Targetpath is not a directory".to_string() ) );
+	}
+
+	let metadata = match fs::metadata(input) {
+		Err( _err ) => return Err( OmError::Generic( "Input not found".to_string() ) ),
+		Ok( md ) => md,
+	};
+
+	if !metadata.is_file() {
+		return Err( OmError::Generic( "Input is not a file".to_string() ) );
+	}
+
+	let mut archive = Archive::create( "" );
+	if let Err( e ) = archive.load( input ) {
+		debug!("Error in load");
+		return Err( e );
+	};
+	if only.is_empty() {
+		archive.unpack( targetpath )
+	} else {
+		let indices = select( &archive, only, paklist )?;
+		archive.unpack_entries( targetpath, &indices )
+	}
+}
+
+fn select(
+		archive:&Archive,
+		only:&[&str],
+		paklist:Option<&str>,
+) -> Result<Vec<usize>,OmError> {
+	let names = match paklist {
+		Some( paklist ) => Helper::crc_names_in_file( paklist )?,
+		None => HashMap::new(),
+	};
+	let selectors = only.iter().map( |s| Selector::parse( s ) ).collect::<Vec<_>>();
+	Selector::select( &selectors, archive, &names )
+}
+
+// writes a single entry to stdout
+fn cat(
+		input:&str,
+		name:&str,
+		paklist:Option<&str>,
+) -> Result<u32,OmError> {
+	let mut archive = Archive::create( "" );
+	archive.load( input )?;
+	let indices = select( &archive, &[ name ], paklist )?;
+	if indices.len() > 1 {
+		return Err( OmError::Generic( format!( "{} matches {} entries, cat needs exactly one", name, indices.len() ) ) );
+	}
+	let entry = &archive.entries[ indices[ 0 ] ];
+	let stdout = std::io::stdout();
+	let mut stdout = stdout.lock();
+	if stdout.write_all( &entry.data ).and_then( |_| stdout.flush() ).is_err() {
+		return Err( OmError::Generic( "Error writing to stdout".to_string() ) );
+	}
+	Ok( 1 )
+}
+
+fn stats(
+		input:&str,
+		paklist:Option<&str>,
+		top:usize,
+		format:&str,
+		output:Option<&str>,
+) -> Result<u32,OmError> {
+	let stats = Stats::load( input, paklist, top )?;
+	match output {
+		Some( output ) => stats.save( output ),
+		None => {
+			match format {
+				"json" => println!( "{}", stats.to_json()? ),
+				_ => print!( "{}", stats.to_text() ),
+			}
+			Ok( stats.files )
+		},
+	}
+}
+
+pub struct Cli {
+}
+
+impl Cli {
+	pub fn app<'a, 'b>( name: &str ) -> App<'a, 'b> {
+		App::new( name )
+			.about("Packs data into archive, or unpacks data from archive")
+			.subcommand(SubCommand::with_name("pack")
+				.arg(Arg::with_name("basepath")
+					.long("basepath")
+					.value_name("BASEPATH")
+					.help("Set the base path (for relative names)")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("output")
+					.long("output")
+					.value_name("OUTPUT")
+					.help("Set the output filename")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("paklist")
+					.long("paklist")
+					.value_name("PAKLIST")
+					.help("Set the pakelist name")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("manifest")
+					.long("manifest")
+					.value_name("MANIFEST")
+					.help("Write a manifest of all entries, as csv for *.csv, json otherwise")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("jobs")
+					.long("jobs")
+					.short("j")
+					.value_name("JOBS")
+					.help("Set the number of worker threads for reading files [default: number of cpus]")
+					.takes_value(true)
+				)
+			)
+			.subcommand(SubCommand::with_name("unpack")
+				.arg(Arg::with_name("targetpath")
+					.long("targetpath")
+					.value_name("TARGETPATH")
+					.help("Set the target path (for relative names)")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("input")
+					.long("input")
+					.value_name("INPUT")
+					.help("Set the input filename")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("only")
+					.long("only")
+					.value_name("NAME|0xCRC|GLOB")
+					.help("Only extract matching entries, can be given multiple times. Globs need --paklist")
+					.takes_value(true)
+					.multiple(true)
+					.number_of_values(1)
+				)
+				.arg(Arg::with_name("paklist")
+					.long("paklist")
+					.value_name("PAKLIST")
+					.help("Set the pakelist name, used to match globs against the entry names")
+					.takes_value(true)
+				)
+			)
+			.subcommand(SubCommand::with_name("cat")
+				.about("Writes a single entry to stdout")
+				.arg(Arg::with_name("input")
+					.long("input")
+					.value_name("INPUT")
+					.help("Set the input filename")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("paklist")
+					.long("paklist")
+					.value_name("PAKLIST")
+					.help("Set the pakelist name, used to match globs against the entry names")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("name")
+					.value_name("NAME|0xCRC|GLOB")
+					.help("The entry to write")
+					.required(true)
+				)
+			)
+			.subcommand(SubCommand::with_name("stats")
+				.about("Shows what takes space in an archive")
+				.arg(Arg::with_name("input")
+					.long("input")
+					.value_name("INPUT")
+					.help("Set the input filename")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("paklist")
+					.long("paklist")
+					.value_name("PAKLIST")
+					.help("Set the pakelist name, used to name the entries")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("top")
+					.long("top")
+					.value_name("TOP")
+					.help("Set the number of largest files to show [default: 10]")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("format")
+					.long("format")
+					.value_name("FORMAT")
+					.help("Set the output format")
+					.possible_values(&["text", "json"])
+					.default_value("text")
+				)
+				.arg(Arg::with_name("output")
+					.long("output")
+					.value_name("OUTPUT")
+					.help("Write the report to a file instead of stdout, as json for *.json, text otherwise")
+					.takes_value(true)
+				)
+			)
+	}
+
+	// returns the exit code
+	pub fn run( matches: &ArgMatches ) -> i32 {
+//		println!("{:?}", matches);
+//		println!("{:?}", matches.subcommand());
+
+		if let ("pack", Some( sub_matches ) ) = matches.subcommand() {
+			let basepath = sub_matches.value_of("basepath").unwrap_or(".").to_string();
+			let output = sub_matches.value_of("output").unwrap_or("out.omar").to_string();
+			let paklist = sub_matches.value_of("paklist").unwrap_or("").to_string();
+			let jobs = match sub_matches.value_of("jobs") {
+				None => Pool::default_jobs(),
+				Some( jobs ) => match jobs.parse::<usize>() {
+					Ok( n ) if n > 0 => n,
+					x => {
+						error!("Error parsing jobs {:?} >{}<", x, jobs );
+						return -1;
+					}
+				},
+			};
+
+			debug!("basepath: {:?}", basepath );
+			debug!("output  : {:?}", output );
+			debug!("paklist : {:?}", paklist );
+			let manifest = sub_matches.value_of("manifest");
+
+			debug!("jobs    : {:?}", jobs );
+			debug!("manifest: {:?}", manifest );
+
+			match packer( &basepath, &paklist, &output, jobs, manifest ) {
+				Ok( number_of_files ) => {
+						info!("{:?} files added to archive", number_of_files );
+						return 0;
+					},
+				Err( e ) => {
+					error!("Error {}", e );
+					return -1;
+				},
+			}
+		}
+
+		if let ("unpack", Some( sub_matches ) ) = matches.subcommand() {
+			let targetpath = sub_matches.value_of("targetpath").unwrap_or(".").to_string();
+			let input = sub_matches.value_of("input").unwrap_or("in.omar").to_string();
+
+
+			let only = sub_matches.values_of("only").map( |o| o.collect::<Vec<_>>() ).unwrap_or_default();
+			let paklist = sub_matches.value_of("paklist");
+
+			debug!("targetpath: {:?}", targetpath );
+			debug!("input  : {:?}", input );
+			debug!("only   : {:?}", only );
+			debug!("paklist: {:?}", paklist );
+			match unpacker( &input, &targetpath, &only, paklist ) {
+				Ok( number_of_files ) => {
+						info!("{:?} files extracted to archive", number_of_files );
+						return 0;
+					},
+				Err( e ) => {
+					error!("Error {}", e );
+					return -1;
+				},
+			}
+		}
+
+		if let ("cat", Some( sub_matches ) ) = matches.subcommand() {
+			let input = sub_matches.value_of("input").unwrap_or("in.omar").to_string();
+			let name = sub_matches.value_of("name").unwrap_or("");
+			let paklist = sub_matches.value_of("paklist");
+
+			debug!("input  : {:?}", input );
+			debug!("name   : {:?}", name );
+			debug!("paklist: {:?}", paklist );
+			match cat( &input, name, paklist ) {
+				Ok( _ ) => return 0,
+				Err( e ) => {
+					error!("Error {}", e );
+					return -1;
+				},
+			}
+		}
+
+		if let ("stats", Some( sub_matches ) ) = matches.subcommand() {
+			let input = sub_matches.value_of("input").unwrap_or("in.omar").to_string();
+			let paklist = sub_matches.value_of("paklist");
+			let format = sub_matches.value_of("format").unwrap_or("text");
+			let output = sub_matches.value_of("output");
+			let top = match sub_matches.value_of("top").unwrap_or("10").parse::<usize>() {
+				Ok( n ) => n,
+				x => {
+					error!("Error parsing top {:?}", x );
+					return -1;
+				}
+			};
+
+			debug!("input  : {:?}", input );
+			debug!("paklist: {:?}", paklist );
+			debug!("top    : {:?}", top );
+			debug!("format : {:?}", format );
+			debug!("output : {:?}", output );
+			match stats( &input, paklist, top, format, output ) {
+				Ok( _ ) => return 0,
+				Err( e ) => {
+					error!("Error {}", e );
+					return -1;
+				},
+			}
+		}
+		-1
+	}
+}
@@ -0,0 +1,98 @@
+use crc::crc32;
+use log::debug;
+use omt_core::{CrcName, OmError, Paths};
+
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+
+#[derive(Debug)]
+pub struct Entry {
+	pub basepath:String,
+	pub filename:String,
+	pub name:String,	// cleaned up filename, used for the crc
+	pub crc:u32,
+	pub size:u32,
+	pub pos:u32,
+	pub hash:u32,	// crc32 of the content, only known after reading the data
+	pub data: Vec<u8>,
+}
+
+impl Entry {
+	pub fn create(basepath:&str, filename:&str) -> Entry {
+
+		let fullfilename = Paths::join( basepath, filename );
+
+		 // :TODO: better error handling
+		let size = match fs::metadata( fullfilename ) {
+			Ok( metadata ) => metadata.len() as u32,
+			Err( _ ) => 0,
+		};
+
+		let clean_name = CrcName::clean( filename );
+		let crc = crc32::checksum_ieee(clean_name.as_bytes());
+		debug!("CRC: {:?} -> {:?} crc: {:?} {:#10X}", filename, clean_name, crc, crc );
+//	      puts "CRC: " + filename + " -> " + name + " crc: " + @crc.to_s
+
+
+		Entry {
+			basepath: basepath.to_string(),
+			filename: filename.to_string(),
+			name: clean_name,
+			crc,
+			size,
+			pos: 0,
+			hash: 0,
+			data: Vec::new(),
+		}
+	}
+
+	pub fn create_from_archive(crc: u32, pos: u32, size: u32) -> Entry {
+		Entry {
+			basepath: String::new(),
+			filename: String::new(),
+			name: String::new(),
+			crc,
+			size,
+			pos,
+			hash: 0,
+			data: Vec::new(),
+		}
+	}
+
+	// reads the content from disk, and returns it together with its checksum
+	pub fn read_data( &self ) -> Result<( Vec<u8>, u32 ),OmError> {
+		let filename = Paths::join( &self.basepath, &self.filename );
+		let mut data_file = match File::open(filename) {
+			Ok( p ) => p,
+			Err( _e ) => return Err( OmError::Generic( "Error reading data file".to_string() ) ),
+		};
+		let mut buffer = Vec::<u8>::with_capacity( self.size as usize );
+		if data_file.read_to_end(&mut buffer).is_err() {
+			return Err( OmError::Generic( "Error reading data file".to_string() ) );
+		}
+		let hash = crc32::checksum_ieee( &buffer );
+		Ok( ( buffer, hash ) )
+	}
+
+	// returns false if the entry doesn't fit into the given data
+	pub fn load_from_archive( &mut self, data: &[u8] ) -> bool {
+		let start = self.pos as usize;
+		let end = match start.checked_add( self.size as usize ) {
+			Some( end ) => end,
+			None => return false,
+		};
+		match data.get( start..end ) {
+			Some( d ) => {
+				self.data = d.to_vec();
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn display(&self) {
+		debug!("Displaying Entry for filename {:?}", self.filename );
+		debug!("{:?}", self);
+	}
+}
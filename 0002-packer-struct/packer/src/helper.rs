@@ -0,0 +1,37 @@
+use omt_core::{CrcName, OmError};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+pub struct Helper {
+
+}
+
+impl Helper {
+	pub fn filenames_in_file(filename: &str) -> Result<Vec<String>, OmError> {
+		let file = File::open(filename);
+
+		let file = match file {
+			Ok( p ) => p,
+			Err( _e ) => return Err( OmError::Generic( "Error reading file".to_string() ) ),
+		};
+
+		let bufreader = BufReader::new(file);
+
+		let mut files: Vec<String> = Vec::new();
+		for ( n, line ) in bufreader.lines().enumerate() {
+			match line {
+				Ok( name ) => files.push( name ),
+				Err( e ) => return Err( OmError::Generic( format!( "Error reading {} line {}: {}", filename, n + 1, e ) ) ),
+			}
+		}
+
+		Ok(files)
+	}
+
+	// maps the crcs back to the names from a paklist, the archive itself only stores the crcs
+	pub fn crc_names_in_file(filename: &str) -> Result<HashMap<u32,String>, OmError> {
+		Ok( Helper::filenames_in_file( filename )?.into_iter().map( |name| ( CrcName::crc( &name ), name ) ).collect() )
+	}
+}
@@ -0,0 +1,29 @@
+pub mod archive;
+pub use archive::Archive;
+
+pub mod cli;
+pub use cli::Cli;
+
+pub mod entry;
+pub use entry::Entry;
+
+pub mod manifest;
+pub use manifest::Manifest;
+
+pub mod helper;
+pub use helper::Helper;
+
+pub mod pool;
+pub use pool::Pool;
+
+pub mod selector;
+pub use selector::Selector;
+
+pub mod stats;
+pub use stats::Stats;
+
+pub mod progress;
+pub use progress::Progress;
+
+pub mod vfs;
+pub use vfs::Vfs;
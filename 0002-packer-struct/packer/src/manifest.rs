@@ -0,0 +1,70 @@
+use omt_core::{OmError, Paths};
+use serde::Serialize;
+
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::Archive;
+
+#[derive(Debug,Serialize)]
+pub struct ManifestEntry {
+	pub source: String,
+	pub name: String,
+	pub crc: String,
+	pub offset: u64,			// absolute position in the archive file
+	pub stored_size: u32,
+	pub original_size: u32,		// same as stored_size until the archive supports compression
+	pub hash: String,			// crc32 of the content
+}
+
+#[derive(Debug,Serialize)]
+pub struct Manifest {
+	pub archive: String,
+	pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+	pub fn create( archive_name: &str, archive: &Archive ) -> Manifest {
+		let data_start = archive.data_start();
+		let entries = archive.entries.iter().map( |e| {
+			ManifestEntry {
+				source: Paths::join( &e.basepath, &e.filename ),
+				name: e.name.clone(),
+				crc: format!( "{:#010X}", e.crc ),
+				offset: data_start + e.pos as u64,
+				stored_size: e.size,
+				original_size: e.size,
+				hash: format!( "{:#010X}", e.hash ),
+			}
+		}).collect();
+
+		Manifest {
+			archive: archive_name.to_string(),
+			entries,
+		}
+	}
+
+	// writes csv for *.csv, json otherwise
+	pub fn save( &self, filename: &str ) -> Result<u32,OmError> {
+		let file = match File::create( filename ) {
+			Ok( f ) => BufWriter::new( f ),
+			Err( _e ) => return Err( OmError::Generic( "Error writing manifest".to_string() ) ),
+		};
+
+		if filename.to_lowercase().ends_with( ".csv" ) {
+			let mut writer = csv::Writer::from_writer( file );
+			for e in &self.entries {
+				if writer.serialize( e ).is_err() {
+					return Err( OmError::Generic( "Error writing manifest".to_string() ) );
+				}
+			}
+			if writer.flush().is_err() {
+				return Err( OmError::Generic( "Error writing manifest".to_string() ) );
+			}
+		} else if serde_json::to_writer_pretty( file, self ).is_err() {
+			return Err( OmError::Generic( "Error writing manifest".to_string() ) );
+		}
+
+		Ok( self.entries.len() as u32 )
+	}
+}
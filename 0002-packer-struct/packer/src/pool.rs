@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+// how far workers may run ahead of the sink, per worker
+const WINDOW_PER_JOB: usize = 4;
+
+#[derive(Default)]
+struct Cursor {
+	next: usize,		// next item to hand out to a worker
+	emitted: usize,		// number of results already passed to the sink
+}
+
+pub struct Pool {
+}
+
+impl Pool {
+	pub fn default_jobs() -> usize {
+		thread::available_parallelism().map( |n| n.get() ).unwrap_or( 1 )
+	}
+
+	// runs work on up to jobs threads, and passes the results to sink in the order of items
+	// workers never get more than a small window ahead of the sink, so memory stays bounded
+	pub fn for_each_ordered<T, R, F, S, E>(
+		items: &[T],
+		jobs: usize,
+		work: F,
+		mut sink: S,
+	) -> Result<(), E>
+	where
+		T: Sync,
+		R: Send,
+		F: Fn( &T ) -> R + Sync,
+		S: FnMut( usize, R ) -> Result<(), E>,
+	{
+		if jobs <= 1 || items.len() <= 1 {
+			for ( i, item ) in items.iter().enumerate() {
+				sink( i, work( item ) )?;
+			}
+			return Ok(());
+		}
+
+		let window = jobs * WINDOW_PER_JOB;
+		let cursor = Mutex::new( Cursor::default() );
+		let cursor_changed = Condvar::new();
+		let abort = AtomicBool::new( false );
+		let ( sender, receiver ) = mpsc::sync_channel( window );
+
+		thread::scope( |scope| {
+			for _ in 0..jobs.min( items.len() ) {
+				let sender = sender.clone();
+				let ( cursor, cursor_changed, abort, work ) = ( &cursor, &cursor_changed, &abort, &work );
+				scope.spawn( move || {
+					loop {
+						let i = {
+							let mut c = cursor.lock().unwrap();
+							while c.next < items.len() && c.next >= c.emitted + window && !abort.load( Ordering::SeqCst ) {
+								c = cursor_changed.wait( c ).unwrap();
+							}
+							if c.next >= items.len() || abort.load( Ordering::SeqCst ) {
+								break;
+							}
+							c.next += 1;
+							c.next - 1
+						};
+						if sender.send( ( i, work( &items[ i ] ) ) ).is_err() {
+							break;
+						}
+					}
+				});
+			}
+			drop( sender );
+
+			let mut pending = BTreeMap::new();
+			let mut emitted = 0;
+			let mut result = Ok(());
+			while emitted < items.len() {
+				let ( i, r ) = match receiver.recv() {
+					Ok( ir ) => ir,
+					Err( _ ) => break,
+				};
+				pending.insert( i, r );
+				while let Some( r ) = pending.remove( &emitted ) {
+					if let Err( e ) = sink( emitted, r ) {
+						result = Err( e );
+						break;
+					}
+					emitted += 1;
+				}
+				if result.is_err() {
+					abort.store( true, Ordering::SeqCst );
+				}
+				cursor.lock().unwrap().emitted = emitted;
+				cursor_changed.notify_all();
+				if result.is_err() {
+					break;
+				}
+			}
+			// unblock workers that are still waiting to send
+			drop( receiver );
+			result
+		})
+	}
+}
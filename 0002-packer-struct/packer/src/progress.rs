@@ -0,0 +1,61 @@
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+const UPDATE_INTERVAL: Duration = Duration::from_millis( 100 );
+
+// single status line on stderr, only shown when stderr is a terminal and not running quiet
+pub struct Progress {
+	label: String,
+	files_total: usize,
+	bytes_total: u64,
+	files: usize,
+	bytes: u64,
+	enabled: bool,
+	last_update: Option<Instant>,
+}
+
+impl Progress {
+	pub fn new( label: &str, files_total: usize, bytes_total: u64 ) -> Progress {
+		Progress {
+			label: label.to_string(),
+			files_total,
+			bytes_total,
+			files: 0,
+			bytes: 0,
+			enabled: std::io::stderr().is_terminal() && log::log_enabled!( log::Level::Info ),
+			last_update: None,
+		}
+	}
+
+	pub fn add( &mut self, bytes: u64 ) {
+		self.files += 1;
+		self.bytes += bytes;
+
+		let now = Instant::now();
+		let due = match self.last_update {
+			None => true,
+			Some( t ) => now.duration_since( t ) >= UPDATE_INTERVAL,
+		};
+		if due || self.files == self.files_total {
+			self.last_update = Some( now );
+			self.print();
+		}
+	}
+
+	pub fn finish( &mut self ) {
+		if self.enabled && self.last_update.is_some() {
+			eprintln!();
+		}
+	}
+
+	fn print( &self ) {
+		if !self.enabled {
+			return;
+		}
+		eprint!(
+			"\r{} {}/{} files, {}/{} bytes",
+			self.label, self.files, self.files_total, self.bytes, self.bytes_total
+		);
+		let _ = std::io::stderr().flush();
+	}
+}
@@ -0,0 +1,74 @@
+use glob::{MatchOptions, Pattern};
+use omt_core::{CrcName, OmError};
+
+use std::collections::HashMap;
+
+use crate::Archive;
+
+// picks entries from an archive by name, crc or glob
+// names go through the crc rule, so they don't have to match the case of the packed file
+// globs are matched against the names from the paklist, since the archive only knows the crcs
+#[derive(Debug)]
+pub enum Selector {
+	Crc( u32 ),
+	Glob( String, Pattern ),
+}
+
+impl Selector {
+	pub fn parse( s: &str ) -> Selector {
+		let hex = s.strip_prefix( "0x" ).or_else( || s.strip_prefix( "0X" ) );
+		if let Some( hex ) = hex {
+			if let Ok( crc ) = u32::from_str_radix( hex.trim(), 16 ) {
+				return Selector::Crc( crc );
+			}
+		}
+		if s.contains( [ '*', '?', '[' ] ) {
+			// names can contain these too, so anything that isn't a valid pattern is taken as a name
+			if let Ok( p ) = Pattern::new( s ) {
+				return Selector::Glob( s.to_string(), p );
+			}
+		}
+		Selector::Crc( CrcName::crc( s ) )
+	}
+
+	fn matches( &self, crc: u32, name: Option<&String> ) -> bool {
+		match self {
+			Selector::Crc( c ) => *c == crc,
+			Selector::Glob( s, p ) => {
+				let options = MatchOptions {
+					case_sensitive: false,
+					..MatchOptions::new()
+				};
+				CrcName::crc( s ) == crc || name.map( |n| p.matches_with( n, options ) ).unwrap_or( false )
+			},
+		}
+	}
+
+	// indices of all entries matching any of the selectors, in archive order
+	// fails if a selector doesn't match anything, so typos don't go unnoticed
+	pub fn select( selectors: &[Selector], archive: &Archive, names: &HashMap<u32,String> ) -> Result<Vec<usize>,OmError> {
+		let mut selected = vec![ false; archive.entries.len() ];
+		for s in selectors {
+			let mut found = false;
+			for ( i, e ) in archive.entries.iter().enumerate() {
+				if s.matches( e.crc, names.get( &e.crc ) ) {
+					selected[ i ] = true;
+					found = true;
+				}
+			}
+			if !found {
+				return Err( OmError::Generic( format!( "No entry matches {}", s ) ) );
+			}
+		}
+		Ok( selected.iter().enumerate().filter( |( _, s )| **s ).map( |( i, _ )| i ).collect() )
+	}
+}
+
+impl std::fmt::Display for Selector {
+	fn fmt( &self, f: &mut std::fmt::Formatter ) -> std::fmt::Result {
+		match self {
+			Selector::Crc( crc ) => write!( f, "{:#010X}", crc ),
+			Selector::Glob( s, _ ) => write!( f, "{}", s ),
+		}
+	}
+}
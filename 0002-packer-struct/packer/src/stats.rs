@@ -0,0 +1,245 @@
+use crc::crc32;
+use omt_core::OmError;
+use serde::Serialize;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::{Archive, Helper};
+
+// name used for entries the paklist doesn't know about
+const UNKNOWN: &str = "(unknown)";
+
+#[derive(Debug,Default,Serialize)]
+pub struct StatsGroup {
+	pub name: String,
+	pub files: u32,
+	pub stored_size: u64,
+	pub original_size: u64,
+	pub compression_ratio: f64,		// stored / original, always 1.0 until the archive supports compression
+}
+
+#[derive(Debug,Serialize)]
+pub struct StatsEntry {
+	pub name: String,
+	pub crc: String,
+	pub size: u32,
+}
+
+#[derive(Debug,Serialize)]
+pub struct StatsDuplicate {
+	pub hash: String,				// crc32 of the content
+	pub size: u32,
+	pub names: Vec<String>,
+	pub wasted: u64,				// bytes that could be saved by storing the content once
+}
+
+#[derive(Debug,Serialize)]
+pub struct Stats {
+	pub archive: String,
+	pub files: u32,
+	pub archive_size: u64,
+	pub header_size: u64,			// header and directory
+	pub stored_size: u64,
+	pub original_size: u64,
+	pub compression_ratio: f64,
+	pub alignment_overhead: u64,	// bytes in the data section not used by any entry
+	pub duplicate_size: u64,
+	pub by_extension: Vec<StatsGroup>,
+	pub by_directory: Vec<StatsGroup>,
+	pub largest: Vec<StatsEntry>,
+	pub duplicates: Vec<StatsDuplicate>,
+}
+
+fn ratio( stored: u64, original: u64 ) -> f64 {
+	if original == 0 {
+		1.0
+	} else {
+		stored as f64 / original as f64
+	}
+}
+
+fn extension_of( name: &str ) -> String {
+	if name.starts_with( "0x" ) && name.len() == 10 {
+		return UNKNOWN.to_string();
+	}
+	let basename = name.rsplit( '/' ).next().unwrap_or( name );
+	match basename.rfind( '.' ) {
+		Some( p ) if p > 0 => basename[ p+1.. ].to_lowercase(),
+		_ => "(none)".to_string(),
+	}
+}
+
+fn directory_of( name: &str ) -> String {
+	if name.starts_with( "0x" ) && name.len() == 10 {
+		return UNKNOWN.to_string();
+	}
+	match name.rfind( '/' ) {
+		Some( p ) => name[ ..p ].to_string(),
+		None => ".".to_string(),
+	}
+}
+
+// groups sorted by size, biggest first
+fn groups( entries: &[( String, u32 )], key: fn( &str ) -> String ) -> Vec<StatsGroup> {
+	let mut groups: BTreeMap<String, StatsGroup> = BTreeMap::new();
+	for ( name, size ) in entries {
+		let k = key( name );
+		let g = groups.entry( k.clone() ).or_insert_with( || StatsGroup { name: k, ..Default::default() } );
+		g.files += 1;
+		g.stored_size += *size as u64;
+		g.original_size += *size as u64;
+	}
+	let mut groups: Vec<StatsGroup> = groups.into_values().map( |mut g| {
+		g.compression_ratio = ratio( g.stored_size, g.original_size );
+		g
+	}).collect();
+	groups.sort_by( |a, b| b.stored_size.cmp( &a.stored_size ).then( a.name.cmp( &b.name ) ) );
+	groups
+}
+
+impl Stats {
+	// names maps crcs to the names from the paklist, entries without a name are shown as their crc
+	pub fn create( archive_name: &str, archive: &Archive, archive_size: u64, names: &HashMap<u32,String>, top: usize ) -> Stats {
+		let entries: Vec<( String, u32 )> = archive.entries.iter().map( |e| {
+			let name = names.get( &e.crc ).cloned().unwrap_or_else( || format!( "{:#010X}", e.crc ) );
+			( name, e.size )
+		}).collect();
+
+		let stored_size: u64 = archive.entries.iter().map( |e| e.size as u64 ).sum();
+		let header_size = archive.data_start();
+
+		// entries may share data, so count every byte of the data section only once
+		let mut ranges: Vec<( u64, u64 )> = archive.entries.iter().map( |e| ( e.pos as u64, e.pos as u64 + e.size as u64 ) ).collect();
+		ranges.sort();
+		let mut used = 0;
+		let mut end = 0;
+		for ( s, e ) in ranges {
+			let s = s.max( end );
+			if e > s {
+				used += e - s;
+				end = e;
+			}
+		}
+		let alignment_overhead = archive_size.saturating_sub( header_size ).saturating_sub( used );
+
+		let mut largest: Vec<StatsEntry> = archive.entries.iter().zip( &entries ).map( |( e, ( name, _ ) )| {
+			StatsEntry {
+				name: name.clone(),
+				crc: format!( "{:#010X}", e.crc ),
+				size: e.size,
+			}
+		}).collect();
+		largest.sort_by( |a, b| b.size.cmp( &a.size ).then( a.name.cmp( &b.name ) ) );
+		largest.truncate( top );
+
+		let mut by_content: BTreeMap<( u32, u32 ), Vec<String>> = BTreeMap::new();
+		for ( e, ( name, _ ) ) in archive.entries.iter().zip( &entries ) {
+			by_content.entry( ( crc32::checksum_ieee( &e.data ), e.size ) ).or_default().push( name.clone() );
+		}
+		let mut duplicates: Vec<StatsDuplicate> = by_content.into_iter().filter( |( _, n )| n.len() > 1 ).map( |( ( hash, size ), mut names )| {
+			names.sort();
+			StatsDuplicate {
+				hash: format!( "{:#010X}", hash ),
+				size,
+				wasted: size as u64 * ( names.len() as u64 - 1 ),
+				names,
+			}
+		}).collect();
+		duplicates.sort_by( |a, b| b.wasted.cmp( &a.wasted ).then( a.names.cmp( &b.names ) ) );
+		let duplicate_size = duplicates.iter().map( |d| d.wasted ).sum();
+
+		Stats {
+			archive: archive_name.to_string(),
+			files: archive.entries.len() as u32,
+			archive_size,
+			header_size,
+			stored_size,
+			original_size: stored_size,
+			compression_ratio: ratio( stored_size, stored_size ),
+			alignment_overhead,
+			duplicate_size,
+			by_extension: groups( &entries, extension_of ),
+			by_directory: groups( &entries, directory_of ),
+			largest,
+			duplicates,
+		}
+	}
+
+	// reads the archive, and the optional paklist for names
+	pub fn load( archive_name: &str, paklist: Option<&str>, top: usize ) -> Result<Stats,OmError> {
+		let mut archive = Archive::create( "" );
+		archive.load( archive_name )?;
+		let archive_size = match std::fs::metadata( archive_name ) {
+			Ok( m ) => m.len(),
+			Err( _e ) => return Err( OmError::Generic( "Error reading file".to_string() ) ),
+		};
+
+		let names = match paklist {
+			Some( paklist ) => Helper::crc_names_in_file( paklist )?,
+			None => HashMap::new(),
+		};
+
+		Ok( Stats::create( archive_name, &archive, archive_size, &names, top ) )
+	}
+
+	pub fn to_text( &self ) -> String {
+		let mut t = String::new();
+		let _ = writeln!( t, "Archive  : {}", self.archive );
+		let _ = writeln!( t, "Files    : {}", self.files );
+		let _ = writeln!( t, "Size     : {} bytes", self.archive_size );
+		let _ = writeln!( t, "  header : {} bytes", self.header_size );
+		let _ = writeln!( t, "  data   : {} bytes stored, {} bytes original, ratio {:.2}", self.stored_size, self.original_size, self.compression_ratio );
+		let _ = writeln!( t, "  padding: {} bytes", self.alignment_overhead );
+		let _ = writeln!( t, "  dupes  : {} bytes", self.duplicate_size );
+
+		for ( title, groups ) in &[ ( "By extension", &self.by_extension ), ( "By directory", &self.by_directory ) ] {
+			let _ = writeln!( t );
+			let _ = writeln!( t, "{}:", title );
+			for g in groups.iter() {
+				let _ = writeln!( t, "\t{:>6} files {:>12} bytes  ratio {:.2}   | {}", g.files, g.stored_size, g.compression_ratio, g.name );
+			}
+		}
+
+		let _ = writeln!( t );
+		let _ = writeln!( t, "Largest:" );
+		for e in &self.largest {
+			let _ = writeln!( t, "\t{:>12} bytes  {}   | {}", e.size, e.crc, e.name );
+		}
+
+		if !self.duplicates.is_empty() {
+			let _ = writeln!( t );
+			let _ = writeln!( t, "Duplicates:" );
+			for d in &self.duplicates {
+				let _ = writeln!( t, "\t{:>12} bytes  {}   | {}", d.size, d.hash, d.names.join( ", " ) );
+			}
+		}
+		t
+	}
+
+	pub fn to_json( &self ) -> Result<String,OmError> {
+		match serde_json::to_string_pretty( self ) {
+			Ok( s ) => Ok( s ),
+			Err( _e ) => Err( OmError::Generic( "Error writing stats".to_string() ) ),
+		}
+	}
+
+	// writes json for *.json, text otherwise
+	pub fn save( &self, filename: &str ) -> Result<u32,OmError> {
+		let text = if filename.to_lowercase().ends_with( ".json" ) {
+			self.to_json()?
+		} else {
+			self.to_text()
+		};
+		let mut file = match File::create( filename ) {
+			Ok( f ) => BufWriter::new( f ),
+			Err( _e ) => return Err( OmError::Generic( "Error writing stats".to_string() ) ),
+		};
+		if std::io::Write::write_all( &mut file, text.as_bytes() ).is_err() {
+			return Err( OmError::Generic( "Error writing stats".to_string() ) );
+		}
+		Ok( self.files )
+	}
+}
@@ -0,0 +1,186 @@
+use log::debug;
+use omt_core::{CrcName, OmError, Paths};
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::{Archive, Helper};
+
+enum Mount {
+	Directory {
+		path: String,
+		index: OnceLock<HashMap<u32,String>>,	// crc -> name, only built when a name isn't found directly
+	},
+	Archive {
+		filename: String,
+		archive: Archive,
+		index: HashMap<u32,usize>,				// crc -> entry
+		names: HashMap<u32,String>,				// crc -> name, from an optional paklist
+	},
+}
+
+impl Mount {
+	fn directory_index<'a>( path: &str, index: &'a OnceLock<HashMap<u32,String>> ) -> &'a HashMap<u32,String> {
+		index.get_or_init( || {
+			debug!("Indexing directory {:?}", path );
+			Paths::files_in_directory( Path::new( path ) ).into_iter().map( |name| {
+				( CrcName::crc( &name ), name )
+			}).collect()
+		})
+	}
+
+	// name on disk for directories, None for archives
+	fn find( &self, name: &str ) -> Option<Option<String>> {
+		match self {
+			Mount::Directory{ path, index } => {
+				if Path::new( &Paths::join( path, name ) ).is_file() {
+					Some( Some( name.to_string() ) )
+				} else {
+					Mount::directory_index( path, index ).get( &CrcName::crc( name ) ).map( |n| Some( n.clone() ) )
+				}
+			},
+			Mount::Archive{ index, .. } => {
+				index.get( &CrcName::crc( name ) ).map( |_| None )
+			},
+		}
+	}
+}
+
+// Reads files the same way the runtime does, from a list of directories and archives.
+// Names are resolved with the crc name rule, so "Foo Bar.PNG" finds "foo bar.png".
+// Mounts are searched in the order they were added, the first match wins.
+#[derive(Default)]
+pub struct Vfs {
+	mounts: Vec<Mount>,
+}
+
+impl Vfs {
+	pub fn new() -> Vfs {
+		Vfs {
+			mounts: Vec::new(),
+		}
+	}
+
+	// mounts a directory, or an archive if path is a file
+	pub fn mount( &mut self, path: &str ) -> Result<u32,OmError> {
+		if Path::new( path ).is_dir() {
+			self.mount_directory( path )
+		} else {
+			self.mount_archive( path, None )
+		}
+	}
+
+	pub fn mount_directory( &mut self, path: &str ) -> Result<u32,OmError> {
+		if !Path::new( path ).is_dir() {
+			return Err( OmError::Generic( format!( "Directory not found {}", path ) ) );
+		}
+		debug!("Mounting directory {:?}", path );
+		self.mounts.push( Mount::Directory {
+			path: path.to_string(),
+			index: OnceLock::new(),
+		});
+		Ok( self.mounts.len() as u32 )
+	}
+
+	// the paklist is optional, and only used to give names to the entries for list
+	pub fn mount_archive( &mut self, filename: &str, paklist: Option<&str> ) -> Result<u32,OmError> {
+		let mut archive = Archive::create( "" );
+		archive.load( filename )?;
+
+		let index = archive.entries.iter().enumerate().map( |( i, e )| ( e.crc, i ) ).collect();
+		let names = match paklist {
+			Some( paklist ) => Helper::crc_names_in_file( paklist )?,
+			None => HashMap::new(),
+		};
+
+		debug!("Mounting archive {:?} with {} entries", filename, archive.entries.len() );
+		self.mounts.push( Mount::Archive {
+			filename: filename.to_string(),
+			archive,
+			index,
+			names,
+		});
+		Ok( self.mounts.len() as u32 )
+	}
+
+	pub fn exists( &self, name: &str ) -> bool {
+		self.mounts.iter().any( |m| m.find( name ).is_some() )
+	}
+
+	pub fn open( &self, name: &str ) -> Result<Box<dyn Read + '_>,OmError> {
+		for m in &self.mounts {
+			match ( m, m.find( name ) ) {
+				( _, None ) => {},
+				( Mount::Directory{ path, .. }, Some( Some( found ) ) ) => {
+					let filename = Paths::join( path, &found );
+					return match File::open( &filename ) {
+						Ok( f ) => Ok( Box::new( f ) ),
+						Err( e ) => Err( OmError::Generic( format!( "Error opening {}: {}", filename, e ) ) ),
+					};
+				},
+				( Mount::Archive{ archive, index, .. }, Some( _ ) ) => {
+					let entry = &archive.entries[ index[ &CrcName::crc( name ) ] ];
+					return Ok( Box::new( Cursor::new( &entry.data[..] ) ) );
+				},
+				( Mount::Directory{ .. }, Some( None ) ) => {},
+			}
+		}
+		Err( OmError::Generic( format!( "File not found {}", name ) ) )
+	}
+
+	pub fn read( &self, name: &str ) -> Result<Vec<u8>,OmError> {
+		let mut data = Vec::new();
+		match self.open( name )?.read_to_end( &mut data ) {
+			Ok( _ ) => Ok( data ),
+			Err( e ) => Err( OmError::Generic( format!( "Error reading {}: {}", name, e ) ) ),
+		}
+	}
+
+	// all names, without the ones hidden by earlier mounts
+	// archive entries without a name from a paklist are listed as their crc, e.g. "0xF9F83871"
+	pub fn list( &self ) -> Vec<String> {
+		let mut seen = HashSet::new();
+		let mut names = Vec::new();
+		for m in &self.mounts {
+			let mount_names: Vec<( u32, String )> = match m {
+				Mount::Directory{ path, index } => {
+					Mount::directory_index( path, index ).iter().map( |( crc, n )| ( *crc, n.clone() ) ).collect()
+				},
+				Mount::Archive{ archive, names, .. } => {
+					archive.entries.iter().map( |e| {
+						let name = names.get( &e.crc ).cloned().unwrap_or_else( || format!( "{:#010X}", e.crc ) );
+						( e.crc, name )
+					}).collect()
+				},
+			};
+			for ( crc, name ) in mount_names {
+				if seen.insert( crc ) {
+					names.push( name );
+				}
+			}
+		}
+		names.sort();
+		names
+	}
+
+	// where a name would be read from, for diagnostics
+	pub fn describe( &self, name: &str ) -> Option<String> {
+		for m in &self.mounts {
+			match ( m, m.find( name ) ) {
+				( _, None ) => {},
+				( Mount::Directory{ path, .. }, Some( Some( found ) ) ) => return Some( Paths::join( path, &found ) ),
+				( Mount::Archive{ filename, .. }, Some( _ ) ) => return Some( format!( "{}:{:#010X}", filename, CrcName::crc( name ) ) ),
+				( Mount::Directory{ .. }, Some( None ) ) => {},
+			}
+		}
+		None
+	}
+
+	pub fn is_empty( &self ) -> bool {
+		self.mounts.is_empty()
+	}
+}
+
@@ -0,0 +1,22 @@
+use packer::Entry;
+
+// pins the CRC name rule, these values have to stay compatible with the old ruby packer
+#[test]
+fn crc_for_tricky_names() {
+	let names = [
+		( "1.txt",							0xF9F83871 ),
+		( "UPPER.TXT",						0x64B5A093 ),
+		( "upper.txt",						0x64B5A093 ),
+		( "Sub Dir/Mixed Case.Txt",			0x814FB060 ),
+		( "with space.txt",					0x61EF8C90 ),
+		( "ünïcödé.txt",					0xC0222A8D ),
+		( " n c d .txt",					0xC0222A8D ),
+		( "punct!#$&'()+,;=@[]^`{}~.txt",	0xD1E2D8FA ),
+		( "dash-under_score%percent.txt",	0xF605B59F ),
+	];
+
+	for ( name, crc ) in &names {
+		let entry = Entry::create( "does-not-exist", name );
+		assert_eq!( entry.crc, *crc, "{:?} -> {:#10X}", name, entry.crc );
+	}
+}
@@ -0,0 +1,26 @@
+use packer::Archive;
+
+use std::fs;
+use std::path::Path;
+
+// replays the checked in fuzz corpus, "valid-*" must load, everything else must be rejected without panicking
+#[test]
+fn load_fuzz_corpus() {
+	let corpus = Path::new( env!("CARGO_MANIFEST_DIR") ).join( "fuzz/corpus/load" );
+	let mut count = 0;
+	for file in fs::read_dir( &corpus ).expect( "Failed reading corpus" ) {
+		let path = file.unwrap().path();
+		let name = path.file_name().unwrap().to_string_lossy().to_string();
+		let data = fs::read( &path ).unwrap();
+
+		let mut archive = Archive::create( "" );
+		let result = archive.load_from_bytes( &data );
+		if name.starts_with( "valid-" ) {
+			assert!( result.is_ok(), "{} should load, got {:?}", name, result );
+		} else {
+			assert!( result.is_err(), "{} should be rejected", name );
+		}
+		count += 1;
+	}
+	assert!( count > 0, "Empty corpus in {:?}", corpus );
+}
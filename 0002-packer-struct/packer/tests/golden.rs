@@ -0,0 +1,151 @@
+use packer::{Archive, Entry, Helper};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// fixture directory, paklist, golden archive
+const FIXTURES: &[(&str, &str, &str)] = &[
+	( "testdata",		"testdata/testdata122334444.paklist",	"testdata/expected-result-test1223334444.omar" ),
+	( "testdata/names",	"testdata/names.paklist",				"testdata/expected-result-names.omar" ),
+];
+
+fn testcase_path( name: &str ) -> String {
+	Path::new( env!("CARGO_MANIFEST_DIR") ).join( ".." ).join( name ).to_string_lossy().to_string()
+}
+
+fn output_path( name: &str ) -> PathBuf {
+	let path = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( name );
+	let _ = fs::remove_dir_all( &path );
+	fs::create_dir_all( &path ).unwrap();
+	path
+}
+
+#[test]
+fn pack_matches_golden() {
+	for ( basepath, paklist, golden ) in FIXTURES {
+		let output = output_path( "pack_matches_golden" ).join( "out.omar" );
+		let output = output.to_string_lossy();
+
+		let mut archive = Archive::create( &testcase_path( basepath ) );
+		for filename in Helper::filenames_in_file( &testcase_path( paklist ) ).unwrap() {
+			archive.add_entry( &filename );
+		}
+		assert_eq!( archive.save( &output ), Ok( archive.entries.len() as u32 ) );
+
+		assert!( fs::read( &*output ).unwrap() == fs::read( testcase_path( golden ) ).unwrap(), "{} differs from {}", output, golden );
+	}
+}
+
+#[test]
+fn pack_with_jobs_matches_golden() {
+	for ( basepath, paklist, golden ) in FIXTURES {
+		let output = output_path( "pack_with_jobs_matches_golden" ).join( "out.omar" );
+
+		let mut archive = Archive::create( &testcase_path( basepath ) );
+		archive.set_jobs( 4 );
+		archive.add_entries( &Helper::filenames_in_file( &testcase_path( paklist ) ).unwrap() );
+		assert!( archive.save( &output.to_string_lossy() ).is_ok() );
+
+		assert!( fs::read( &output ).unwrap() == fs::read( testcase_path( golden ) ).unwrap(), "{:?} differs from {}", output, golden );
+		for entry in &archive.entries {
+			let data = fs::read( format!( "{}/{}", testcase_path( basepath ), entry.filename ) ).unwrap();
+			assert_eq!( entry.hash, crc::crc32::checksum_ieee( &data ), "{}", entry.filename );
+		}
+	}
+}
+
+// many small files of different sizes, so workers finish out of order
+#[test]
+fn pack_with_jobs_is_deterministic() {
+	let basepath = output_path( "pack_with_jobs_is_deterministic" );
+	let filenames = ( 0..500 ).map( |i| {
+		let filename = format!( "{}.bin", i );
+		fs::write( basepath.join( &filename ), vec![ ( i % 251 ) as u8; ( i * 7919 ) % 4096 ] ).unwrap();
+		filename
+	}).collect::<Vec<_>>();
+
+	let mut outputs = Vec::new();
+	for jobs in &[ 1, 3, 8 ] {
+		let output = output_path( &format!( "pack_with_jobs_is_deterministic_{}", jobs ) ).join( "out.omar" );
+		let mut archive = Archive::create( &basepath.to_string_lossy() );
+		archive.set_jobs( *jobs );
+		archive.add_entries( &filenames );
+		assert_eq!( archive.save( &output.to_string_lossy() ), Ok( filenames.len() as u32 ) );
+		outputs.push( fs::read( &output ).unwrap() );
+	}
+	assert!( outputs[ 0 ] == outputs[ 1 ] );
+	assert!( outputs[ 0 ] == outputs[ 2 ] );
+}
+
+#[test]
+fn pack_command_matches_golden() {
+	for ( basepath, paklist, golden ) in FIXTURES {
+		let output = output_path( "pack_command_matches_golden" ).join( "out.omar" );
+
+		let status = Command::new( env!("CARGO_BIN_EXE_packer") )
+						.arg( "pack" )
+						.arg( "--basepath" ).arg( testcase_path( basepath ) )
+						.arg( "--paklist" ).arg( testcase_path( paklist ) )
+						.arg( "--output" ).arg( &output )
+						.output()
+						.unwrap()
+						.status;
+		assert!( status.success() );
+
+		assert!( fs::read( &output ).unwrap() == fs::read( testcase_path( golden ) ).unwrap(), "{:?} differs from {}", output, golden );
+	}
+}
+
+#[test]
+fn load_golden_round_trips() {
+	for ( basepath, paklist, golden ) in FIXTURES {
+		let mut archive = Archive::create( "" );
+		assert!( archive.load( &testcase_path( golden ) ).is_ok() );
+
+		let filenames = Helper::filenames_in_file( &testcase_path( paklist ) ).unwrap();
+		assert_eq!( archive.entries.len(), filenames.len() );
+
+		let mut pos = 0;
+		for ( entry, filename ) in archive.entries.iter().zip( filenames.iter() ) {
+			let source = Entry::create( &testcase_path( basepath ), filename );
+			let data = fs::read( format!( "{}/{}", testcase_path( basepath ), filename ) ).unwrap();
+
+			assert_eq!( entry.crc, source.crc, "{}", filename );
+			assert_eq!( entry.pos, pos, "{}", filename );
+			assert_eq!( entry.size as usize, data.len(), "{}", filename );
+			assert_eq!( entry.data, data, "{}", filename );
+			pos += entry.size;
+		}
+	}
+}
+
+#[test]
+fn unpack_golden_round_trips() {
+	for ( basepath, paklist, golden ) in FIXTURES {
+		let targetpath = output_path( "unpack_golden_round_trips" );
+
+		let mut archive = Archive::create( "" );
+		assert!( archive.load( &testcase_path( golden ) ).is_ok() );
+		assert!( archive.unpack( &targetpath.to_string_lossy() ).is_ok() );
+
+		let filenames = Helper::filenames_in_file( &testcase_path( paklist ) ).unwrap();
+		assert_eq!( fs::read_dir( &targetpath ).unwrap().count(), filenames.len() );
+		for ( entry, filename ) in archive.entries.iter().zip( filenames.iter() ) {
+			let unpacked = fs::read( targetpath.join( format!( "{:#10X}", entry.crc ) ) ).unwrap();
+			let source = fs::read( format!( "{}/{}", testcase_path( basepath ), filename ) ).unwrap();
+			assert_eq!( unpacked, source, "{}", filename );
+		}
+
+		// and pack them up again, unpacked files are named after the crc, so restore the original one
+		let mut repacked = Archive::create( &targetpath.to_string_lossy() );
+		for entry in &archive.entries {
+			let name = format!( "{:#10X}", entry.crc );
+			repacked.add_entry( &name );
+			repacked.entries.last_mut().unwrap().crc = entry.crc;
+		}
+		let output = output_path( "unpack_golden_round_trips_repacked" ).join( "out.omar" );
+		assert!( repacked.save( &output.to_string_lossy() ).is_ok() );
+		assert!( fs::read( &output ).unwrap() == fs::read( testcase_path( golden ) ).unwrap(), "repacked {} differs", golden );
+	}
+}
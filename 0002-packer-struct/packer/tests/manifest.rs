@@ -0,0 +1,45 @@
+use packer::{Archive, Helper, Manifest};
+
+use std::fs;
+use std::path::Path;
+
+fn testcase_path( name: &str ) -> String {
+	Path::new( env!("CARGO_MANIFEST_DIR") ).join( ".." ).join( name ).to_string_lossy().to_string()
+}
+
+#[test]
+fn manifest_maps_crcs_back_to_files() {
+	let output_path = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "manifest_maps_crcs_back_to_files" );
+	let _ = fs::remove_dir_all( &output_path );
+	fs::create_dir_all( &output_path ).unwrap();
+	let output = output_path.join( "names.omar" ).to_string_lossy().to_string();
+	let json = output_path.join( "names.json" ).to_string_lossy().to_string();
+	let csv = output_path.join( "names.csv" ).to_string_lossy().to_string();
+
+	let mut archive = Archive::create( &testcase_path( "testdata/names" ) );
+	archive.add_entries( &Helper::filenames_in_file( &testcase_path( "testdata/names.paklist" ) ).unwrap() );
+	archive.save( &output ).unwrap();
+
+	let manifest = Manifest::create( &output, &archive );
+	assert_eq!( manifest.save( &json ), Ok( 6 ) );
+	assert_eq!( manifest.save( &csv ), Ok( 6 ) );
+
+	let packed = fs::read( &output ).unwrap();
+	let json: serde_json::Value = serde_json::from_slice( &fs::read( &json ).unwrap() ).unwrap();
+	let entries = json[ "entries" ].as_array().unwrap();
+	assert_eq!( entries.len(), 6 );
+	for e in entries {
+		let source = fs::read( e[ "source" ].as_str().unwrap() ).unwrap();
+		let offset = e[ "offset" ].as_u64().unwrap() as usize;
+		let size = e[ "stored_size" ].as_u64().unwrap() as usize;
+		assert_eq!( &packed[ offset..offset+size ], &source[..] );
+		assert_eq!( e[ "hash" ].as_str().unwrap(), format!( "{:#010X}", crc::crc32::checksum_ieee( &source ) ) );
+	}
+	assert_eq!( entries[ 0 ][ "name" ], "upper.txt" );
+	assert_eq!( entries[ 0 ][ "crc" ], "0x64B5A093" );
+
+	let csv = fs::read_to_string( &csv ).unwrap();
+	let mut lines = csv.lines();
+	assert_eq!( lines.next(), Some( "source,name,crc,offset,stored_size,original_size,hash" ) );
+	assert_eq!( lines.count(), 6 );
+}
@@ -0,0 +1,81 @@
+use packer::{Archive, Helper, Selector};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn testcase_path( name: &str ) -> String {
+	Path::new( env!("CARGO_MANIFEST_DIR") ).join( ".." ).join( name ).to_string_lossy().to_string()
+}
+
+fn names_archive() -> Archive {
+	let mut archive = Archive::create( "" );
+	archive.load( &testcase_path( "testdata/expected-result-names.omar" ) ).unwrap();
+	archive
+}
+
+fn select( archive: &Archive, selectors: &[&str], names: &HashMap<u32,String> ) -> Vec<u32> {
+	let selectors = selectors.iter().map( |s| Selector::parse( s ) ).collect::<Vec<_>>();
+	Selector::select( &selectors, archive, names ).unwrap().iter().map( |i| archive.entries[ *i ].crc ).collect()
+}
+
+#[test]
+fn select_by_name_crc_and_glob() {
+	let archive = names_archive();
+	let none = HashMap::new();
+	let names = Helper::crc_names_in_file( &testcase_path( "testdata/names.paklist" ) ).unwrap();
+
+	assert_eq!( select( &archive, &[ "SUB DIR/mixed case.txt" ], &none ), vec![ 0x814FB060 ] );
+	assert_eq!( select( &archive, &[ "0x814fb060" ], &none ), vec![ 0x814FB060 ] );
+	assert_eq!( select( &archive, &[ "punct!#$&'()+,;=@[]^`{}~.txt" ], &none ), vec![ 0xD1E2D8FA ] );
+	assert_eq!( select( &archive, &[ "sub dir/*" ], &names ), vec![ 0x814FB060 ] );
+	assert_eq!( select( &archive, &[ "*.TXT" ], &names ).len(), 6 );
+	// archive order, no duplicates
+	assert_eq!( select( &archive, &[ "sub dir/*", "upper.txt", "0x64B5A093" ], &names ), vec![ 0x64B5A093, 0x814FB060 ] );
+
+	let missing = [ Selector::parse( "missing.txt" ) ];
+	assert!( Selector::select( &missing, &archive, &names ).is_err() );
+	// globs need names
+	let glob = [ Selector::parse( "*.txt" ) ];
+	assert!( Selector::select( &glob, &archive, &none ).is_err() );
+}
+
+#[test]
+fn unpack_only_and_cat_commands() {
+	let targetpath = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "unpack_only_and_cat_commands" );
+	let _ = fs::remove_dir_all( &targetpath );
+	fs::create_dir_all( &targetpath ).unwrap();
+
+	let status = Command::new( env!("CARGO_BIN_EXE_packer") )
+					.arg( "unpack" )
+					.arg( "--input" ).arg( testcase_path( "testdata/expected-result-names.omar" ) )
+					.arg( "--paklist" ).arg( testcase_path( "testdata/names.paklist" ) )
+					.arg( "--targetpath" ).arg( &targetpath )
+					.arg( "--only" ).arg( "Sub Dir/*" )
+					.arg( "--only" ).arg( "0x64B5A093" )
+					.status()
+					.unwrap();
+	assert!( status.success() );
+	let mut unpacked = fs::read_dir( &targetpath ).unwrap().map( |e| e.unwrap().file_name().to_string_lossy().to_string() ).collect::<Vec<_>>();
+	unpacked.sort();
+	assert_eq!( unpacked, vec![ "0x64B5A093", "0x814FB060" ] );
+
+	let output = Command::new( env!("CARGO_BIN_EXE_packer") )
+					.arg( "cat" )
+					.arg( "--input" ).arg( testcase_path( "testdata/expected-result-names.omar" ) )
+					.arg( "with SPACE.txt" )
+					.output()
+					.unwrap();
+	assert!( output.status.success() );
+	assert_eq!( output.stdout, fs::read( testcase_path( "testdata/names/with space.txt" ) ).unwrap() );
+
+	let output = Command::new( env!("CARGO_BIN_EXE_packer") )
+					.arg( "cat" )
+					.arg( "--input" ).arg( testcase_path( "testdata/expected-result-names.omar" ) )
+					.arg( "missing.txt" )
+					.output()
+					.unwrap();
+	assert!( !output.status.success() );
+	assert!( output.stdout.is_empty() );
+}
@@ -0,0 +1,61 @@
+use packer::{Archive, Stats};
+
+use std::fs;
+use std::path::Path;
+
+fn testcase_path( name: &str ) -> String {
+	Path::new( env!("CARGO_MANIFEST_DIR") ).join( ".." ).join( name ).to_string_lossy().to_string()
+}
+
+#[test]
+fn stats_groups_and_duplicates() {
+	let output_path = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "stats_groups_and_duplicates" );
+	let _ = fs::remove_dir_all( &output_path );
+	fs::create_dir_all( output_path.join( "sub" ) ).unwrap();
+	fs::write( output_path.join( "a.txt" ), b"same content" ).unwrap();
+	fs::write( output_path.join( "sub/b.txt" ), b"same content" ).unwrap();
+	fs::write( output_path.join( "sub/c.png" ), b"x" ).unwrap();
+	fs::write( output_path.join( "list.paklist" ), "a.txt\nsub/b.txt\nsub/c.png\n" ).unwrap();
+	let basepath = output_path.to_string_lossy().to_string();
+	let output = output_path.join( "out.omar" ).to_string_lossy().to_string();
+	let paklist = output_path.join( "list.paklist" ).to_string_lossy().to_string();
+
+	let mut archive = Archive::create( &basepath );
+	for name in &[ "a.txt", "sub/b.txt", "sub/c.png" ] {
+		archive.add_entry( name );
+	}
+	archive.save( &output ).unwrap();
+
+	let stats = Stats::load( &output, Some( &paklist ), 2 ).unwrap();
+	assert_eq!( stats.files, 3 );
+	assert_eq!( stats.stored_size, 25 );
+	assert_eq!( stats.archive_size, stats.header_size + stats.stored_size );
+	assert_eq!( stats.alignment_overhead, 0 );
+	assert_eq!( stats.compression_ratio, 1.0 );
+
+	assert_eq!( stats.by_extension.len(), 2 );
+	assert_eq!( stats.by_extension[ 0 ].name, "txt" );
+	assert_eq!( stats.by_extension[ 0 ].files, 2 );
+	assert_eq!( stats.by_directory[ 0 ].name, "sub" );
+	assert_eq!( stats.by_directory[ 0 ].stored_size, 13 );
+
+	assert_eq!( stats.largest.len(), 2 );
+	assert_eq!( stats.largest[ 0 ].name, "a.txt" );
+
+	assert_eq!( stats.duplicates.len(), 1 );
+	assert_eq!( stats.duplicates[ 0 ].names, vec![ "a.txt", "sub/b.txt" ] );
+	assert_eq!( stats.duplicate_size, 12 );
+
+	let json: serde_json::Value = serde_json::from_str( &stats.to_json().unwrap() ).unwrap();
+	assert_eq!( json[ "duplicates" ][ 0 ][ "wasted" ], 12 );
+}
+
+#[test]
+fn stats_without_paklist_uses_crcs() {
+	let stats = Stats::load( &testcase_path( "testdata/expected-result-test1223334444.omar" ), None, 10 ).unwrap();
+	assert_eq!( stats.files, 4 );
+	assert!( stats.largest.iter().any( |e| e.name == "0xF9F83871" ) );
+	assert_eq!( stats.by_extension.len(), 1 );
+	assert_eq!( stats.by_extension[ 0 ].name, "(unknown)" );
+	assert!( stats.to_text().contains( "Files    : 4" ) );
+}
@@ -0,0 +1,89 @@
+use packer::Archive;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn output_path( name: &str ) -> PathBuf {
+	let path = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( name );
+	let _ = fs::remove_dir_all( &path );
+	fs::create_dir_all( path.join( "data/dir" ) ).unwrap();
+	fs::write( path.join( "data/good.txt" ), b"good" ).unwrap();
+	fs::write( path.join( "outside.txt" ), b"outside" ).unwrap();
+	path
+}
+
+#[test]
+fn validate_reports_all_problems() {
+	let path = output_path( "validate_reports_all_problems" );
+	let mut archive = Archive::create( &path.join( "data" ).to_string_lossy() );
+	for name in &[ "good.txt", "missing.txt", "dir", "../outside.txt", "dir/../good.txt" ] {
+		archive.add_entry( name );
+	}
+
+	let e = archive.validate().unwrap_err().to_string();
+	assert!( e.contains( "3 of 5 files" ), "{}", e );
+	assert!( e.contains( "missing.txt: not found" ), "{}", e );
+	assert!( e.contains( "dir: not a regular file" ), "{}", e );
+	assert!( e.contains( "../outside.txt: outside of basepath" ), "{}", e );
+	assert!( !e.contains( "good.txt" ), "{}", e );
+}
+
+#[test]
+fn failed_pack_keeps_existing_output() {
+	let path = output_path( "failed_pack_keeps_existing_output" );
+	let output = path.join( "out.omar" );
+	fs::write( &output, b"previous" ).unwrap();
+	fs::write( path.join( "list.paklist" ), "good.txt\nmissing.txt\n" ).unwrap();
+
+	let status = Command::new( env!("CARGO_BIN_EXE_packer") )
+					.arg( "pack" )
+					.arg( "--basepath" ).arg( path.join( "data" ) )
+					.arg( "--paklist" ).arg( path.join( "list.paklist" ) )
+					.arg( "--output" ).arg( &output )
+					.status()
+					.unwrap();
+	assert!( !status.success() );
+	assert_eq!( fs::read( &output ).unwrap(), b"previous" );
+
+	fs::write( path.join( "list.paklist" ), "good.txt\n" ).unwrap();
+	let status = Command::new( env!("CARGO_BIN_EXE_packer") )
+					.arg( "pack" )
+					.arg( "--basepath" ).arg( path.join( "data" ) )
+					.arg( "--paklist" ).arg( path.join( "list.paklist" ) )
+					.arg( "--output" ).arg( &output )
+					.status()
+					.unwrap();
+	assert!( status.success() );
+	assert!( fs::read( &output ).unwrap().starts_with( b"OMAR" ) );
+
+	// no temporary files left behind
+	let mut files = fs::read_dir( &path ).unwrap().map( |e| e.unwrap().file_name().to_string_lossy().to_string() ).collect::<Vec<_>>();
+	files.sort();
+	assert_eq!( files, vec![ "data", "list.paklist", "out.omar", "outside.txt" ] );
+}
+
+#[cfg(unix)]
+#[test]
+fn validate_rejects_symlink_to_outside() {
+	let path = output_path( "validate_rejects_symlink_to_outside" );
+	std::os::unix::fs::symlink( path.join( "outside.txt" ), path.join( "data/link.txt" ) ).unwrap();
+	std::os::unix::fs::symlink( path.join( "data/good.txt" ), path.join( "data/dir/inside.txt" ) ).unwrap();
+	let mut archive = Archive::create( &path.join( "data" ).to_string_lossy() );
+	archive.add_entry( "link.txt" );
+	archive.add_entry( "dir/inside.txt" );
+
+	let e = archive.validate().unwrap_err().to_string();
+	assert!( e.contains( "1 of 2 files" ), "{}", e );
+	assert!( e.contains( "link.txt: symlink to outside of basepath" ), "{}", e );
+}
+
+#[test]
+fn paklist_with_invalid_utf8_is_an_error() {
+	let path = output_path( "paklist_with_invalid_utf8_is_an_error" );
+	let paklist = path.join( "list.paklist" );
+	fs::write( &paklist, b"good.txt\n\xff\xfe.txt\n" ).unwrap();
+
+	let e = packer::Helper::filenames_in_file( &paklist.to_string_lossy() ).unwrap_err().to_string();
+	assert!( e.contains( "line 2" ), "{}", e );
+}
@@ -0,0 +1,83 @@
+use packer::Vfs;
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+fn testcase_path( name: &str ) -> String {
+	Path::new( env!("CARGO_MANIFEST_DIR") ).join( ".." ).join( name ).to_string_lossy().to_string()
+}
+
+fn output_path( name: &str ) -> String {
+	let path = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( name );
+	let _ = fs::remove_dir_all( &path );
+	fs::create_dir_all( &path ).unwrap();
+	path.to_string_lossy().to_string()
+}
+
+#[test]
+fn directory_resolves_names_like_the_archive() {
+	let mut vfs = Vfs::new();
+	vfs.mount_directory( &testcase_path( "testdata/names" ) ).unwrap();
+
+	assert!( vfs.exists( "UPPER.TXT" ) );
+	assert!( vfs.exists( "upper.txt" ) );
+	assert!( vfs.exists( "sub dir/mixed case.txt" ) );
+	assert!( !vfs.exists( "missing.txt" ) );
+
+	let expected = fs::read( testcase_path( "testdata/names/Sub Dir/Mixed Case.Txt" ) ).unwrap();
+	assert_eq!( vfs.read( "SUB DIR/MIXED CASE.TXT" ), Ok( expected ) );
+	assert!( vfs.read( "missing.txt" ).is_err() );
+}
+
+#[test]
+fn archive_reads_entries() {
+	let mut vfs = Vfs::new();
+	vfs.mount_archive(
+		&testcase_path( "testdata/expected-result-names.omar" ),
+		Some( &testcase_path( "testdata/names.paklist" ) ),
+	).unwrap();
+
+	for name in packer::Helper::filenames_in_file( &testcase_path( "testdata/names.paklist" ) ).unwrap() {
+		let mut data = Vec::new();
+		vfs.open( &name ).unwrap().read_to_end( &mut data ).unwrap();
+		assert_eq!( data, fs::read( testcase_path( &format!( "testdata/names/{}", name ) ) ).unwrap(), "{}", name );
+	}
+
+	let mut listed = vfs.list();
+	let mut expected = packer::Helper::filenames_in_file( &testcase_path( "testdata/names.paklist" ) ).unwrap();
+	listed.sort();
+	expected.sort();
+	assert_eq!( listed, expected );
+}
+
+#[test]
+fn archive_without_paklist_lists_crcs() {
+	let mut vfs = Vfs::new();
+	vfs.mount( &testcase_path( "testdata/expected-result-test1223334444.omar" ) ).unwrap();
+
+	assert!( vfs.exists( "1.txt" ) );
+	assert!( vfs.list().contains( &"0xF9F83871".to_string() ) );
+}
+
+#[test]
+fn first_mount_wins() {
+	let overlay = output_path( "vfs_first_mount_wins" );
+	fs::write( format!( "{}/1.txt", overlay ), b"overlay" ).unwrap();
+
+	let mut vfs = Vfs::new();
+	vfs.mount( &overlay ).unwrap();
+	vfs.mount( &testcase_path( "testdata/expected-result-test1223334444.omar" ) ).unwrap();
+
+	assert_eq!( vfs.read( "1.txt" ), Ok( b"overlay".to_vec() ) );
+	assert_eq!( vfs.read( "22.txt" ), Ok( fs::read( testcase_path( "testdata/22.txt" ) ).unwrap() ) );
+	assert_eq!( vfs.list().len(), 4 );
+}
+
+#[test]
+fn mount_missing_fails() {
+	let mut vfs = Vfs::new();
+	assert!( vfs.mount( &testcase_path( "testdata/missing.omar" ) ).is_err() );
+	assert!( vfs.mount_directory( &testcase_path( "testdata/missing" ) ).is_err() );
+	assert!( vfs.is_empty() );
+}
@@ -1,6 +1,6 @@
 #!/bin/sh
 
-OMT_PACKER=packer/target/debug/packer
+OMT_PACKER=../target/debug/packer
 
 ${OMT_PACKER} unpack --input testdata/test1223334444.omar --targetpath unpackeddata
 
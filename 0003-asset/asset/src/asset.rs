@@ -0,0 +1,702 @@
+use atlas::Atlas;
+use glob::glob;
+use log::{debug, error, info, trace, warn};
+use omt_core::{OmError, Paths};
+use packer::{Archive, Helper, Vfs};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Instant;
+use yaml_rust::YamlLoader;
+
+use crate::{AssetBuild, AssetConfig, BuildDb, BuildReport, ConfigCheck, BuildGraph, BuildRecord, CmdLine, ParameterValue, RunLog, RunReport, RunStatus, ToolRun};
+
+enum RunResult {
+	UpToDate,
+	Done( u32, BuildRecord, RunLog ),
+	Failed( OmError, RunLog ),
+}
+
+pub struct Asset{
+}
+
+impl Asset{
+
+	// inputs below the content directory go through the vfs, so they can also come from mounts
+	fn read_input(
+		asset_build: &AssetBuild,
+		vfs: &Vfs,
+		filename: &str,
+	)
+	-> Result<Vec<u8>,OmError> {
+		match Paths::relative( Path::new( filename ), Path::new( &asset_build.content_directory ) ) {
+			Some( name ) => vfs.read( &name ),
+			None => fs::read( filename ).map_err( |e| OmError::Generic( format!( "Error reading {}: {}", filename, e ) ) ),
+		}
+	}
+
+	// the output in the data directory, with the directories leading to it already created
+	fn output_path(
+		asset_build: &AssetBuild,
+		tool_run: &ToolRun,
+	)
+	-> Result<String,OmError> {
+		if tool_run.output.is_empty() {
+			return Err( OmError::Generic( format!( "No output for asset tool command {:?}", tool_run.command ) ) );
+		}
+		let output = Paths::join( &asset_build.data_directory, &tool_run.output );
+		if let Some( parent ) = Path::new( &output ).parent() {
+			let _ = fs::create_dir_all( parent );
+		}
+		Ok( output )
+	}
+
+	pub fn tool_asset(
+		asset_build: &AssetBuild,
+		vfs: &Vfs,
+		tool_run: &ToolRun,
+		log: &mut RunLog,
+	)
+	-> Result<u32,OmError> {
+		match tool_run.command.as_ref() {
+			"" => {
+				Err( OmError::Generic( "NO command for asset tool".to_string() ) )
+			},
+			"dump" => {
+				log.add( &format!( "command.  : {:?}", tool_run.command ) );
+				log.add( &format!( "output    : {:?}", tool_run.output ) );
+				log.add( &format!( "input     : {:?}", tool_run.input ) );
+				log.add( &format!( "parameters: {:?}", tool_run.parameters ) );
+				Ok(0)
+			},
+			"copy" => {
+				let source = match tool_run.input_files().into_iter().next() {
+					Some( source ) => source,
+					None => return Err( OmError::Generic( "No input for copy".to_string() ) ),
+				};
+				let dest = Asset::output_path( asset_build, tool_run )?;
+				let copied = Asset::read_input( asset_build, vfs, &source ).and_then( |data| {
+					match fs::write( &dest, &data ) {
+						Ok( _ ) => Ok( data.len() as u64 ),
+						Err( e ) => Err( OmError::Generic( e.to_string() ) ),
+					}
+				});
+				match copied {
+					Ok( bytes ) => {
+						log.add( &format!( "Copied {:?} bytes from {:?} to {:?}", bytes, &source, &dest ) );
+						log.wrote( &dest );
+						let number_of_assets_updated = 1;
+						Ok( number_of_assets_updated )
+					},
+					Err( e ) => {
+						Err( OmError::Generic( format!( "Error copying from {:?} to {:?}: {}", &source, &dest, e ) ) )
+					},
+				}
+			},
+			"atlas" => {
+				// output is a pattern like "atlas-%d", one atlas is written for every %d needed
+				let output = Asset::output_path( asset_build, tool_run )?;
+				let size = tool_run.integer_parameter( "size", 2048 )?;
+				let border = tool_run.integer_parameter( "border", 0 )?;
+				let mut images = Vec::new();
+				for i in tool_run.input_files() {
+					let data = Asset::read_input( asset_build, vfs, &i )?;
+					images.push( ( i, data ) );
+				}
+				log.add( &format!( "Combining {} images into {:?}, size {}, border {}", images.len(), output, size, border ) );
+				let n = Atlas::combine_data( &output, size as u32, border as u32, &images )?;
+				log.add( &format!( "{} atlases created", n ) );
+				for f in Atlas::filenames( &output, n ) {
+					log.wrote( &f );
+				}
+				Ok( n )
+			},
+			"pack" => {
+				// entry names are relative to the basepath, which defaults to the data directory
+				let output = Asset::output_path( asset_build, tool_run )?;
+				let basepath = tool_run.string_parameter( "basepath" )?.unwrap_or_else( || asset_build.data_directory.clone() );
+				// one paklist, or a list of them, packed in the order given
+				let paklists = tool_run.list_parameter( "paklist" )?;
+				let mut names = Vec::new();
+				for p in &paklists {
+					match p.as_str() {
+						Some( p ) => names.extend( Helper::filenames_in_file( p )? ),
+						None => return Err( OmError::Generic( format!( "Parameter paklist is not a list of files: {}", p ) ) ),
+					}
+				}
+				if paklists.is_empty() {
+					for i in tool_run.input_files() {
+						let normalized = Paths::normalize( &i );
+						match Paths::relative( Path::new( &normalized ), Path::new( &Paths::normalize( &basepath ) ) ) {
+							Some( name ) => names.push( name ),
+							None => return Err( OmError::Generic( format!( "Input {} is outside of basepath {}", i, basepath ) ) ),
+						}
+					}
+				}
+				let mut archive = Archive::create( &basepath );
+				archive.add_entries( &names );
+				let n = archive.save( &output )?;
+				log.add( &format!( "{} files packed into {:?}", n, output ) );
+				log.wrote( &output );
+				Ok( 1 )
+			},
+			cmd => {
+				Err( OmError::Generic( format!( "Unhandled asset tool command: {:?}", cmd ) ) )
+			},
+		}
+	}
+
+	// the final step of a build, everything the build wrote to the data directory goes into the paklist and the archive
+	// except the archives written by tool runs, which are only known so they aren't reported as stale
+	// both are sorted, relative to the data directory
+	fn write_paklist_and_archive(
+		asset_build: &AssetBuild,
+		names: &[String],
+		archives: &[String],
+	)
+	-> Result<u32,OmError> {
+		let data_directory = &asset_build.data_directory;
+
+		// the paklist and archive might live in the data directory too
+		let excluded = [ &asset_build.paklist, &asset_build.archive ].iter().filter( |f| !f.is_empty() ).map( |f| Paths::normalize( f ) ).collect::<Vec<_>>();
+		for stale in Paths::files_in_directory( Path::new( data_directory ) ) {
+			if names.binary_search( &stale ).is_err() && archives.binary_search( &stale ).is_err() && !excluded.contains( &Paths::normalize( &Paths::join( data_directory, &stale ) ) ) {
+				warn!("📁 Stale file in data directory, not written by any tool run: {}", stale );
+			}
+		}
+
+		if !asset_build.paklist.is_empty() {
+			let mut paklist = names.join( "\n" );
+			paklist.push( '\n' );
+			if let Err( e ) = fs::write( &asset_build.paklist, paklist ) {
+				return Err( OmError::Generic( format!( "Error writing paklist {}: {}", asset_build.paklist, e ) ) );
+			}
+			info!("📁 📝 {} files listed in {:?}", names.len(), asset_build.paklist );
+		}
+
+		if !asset_build.archive.is_empty() {
+			if let Some( parent ) = Path::new( &asset_build.archive ).parent() {
+				let _ = fs::create_dir_all( parent );
+			}
+			let mut archive = Archive::create( data_directory );
+			archive.set_jobs( asset_build.jobs );
+			archive.add_entries( names );
+			let n = archive.save( &asset_build.archive )?;
+			info!("📁 📦 {} files packed into {:?}", n, asset_build.archive );
+		}
+
+		Ok( names.len() as u32 )
+	}
+
+	pub fn tool_call_external(
+		asset_build: &AssetBuild,
+		tool_run: &ToolRun,
+		log: &mut RunLog,
+	)
+	-> Result<u32,OmError> {
+		// tools only write their output, the directories leading to it are ours
+		if !tool_run.output.is_empty() {
+			Asset::output_path( asset_build, tool_run )?;
+		}
+		let mut command = if tool_run.shell {
+			let cmd_line = CmdLine::shell_line( tool_run, &asset_build.data_directory )?;
+			log.cmd_line = cmd_line.clone();
+			let mut command = Command::new("/bin/sh");
+			command.args(["-c", &cmd_line]);
+			command
+		} else {
+			let args = CmdLine::args( tool_run, &asset_build.data_directory )?;
+			if args.is_empty() {
+				log.add( "No cmd_line, nothing to run" );
+				return Ok( 0 );
+			}
+			log.cmd_line = args.iter().map( |a| CmdLine::quote( a ) ).collect::<Vec<_>>().join( " " );
+			let mut command = Command::new( &args[ 0 ] );
+			command.args( &args[ 1.. ] );
+			command
+		};
+		log.add( &format!( "Calling\n{}", log.cmd_line.clone() ) );
+
+		match command.output() {
+			Err(e) => {
+				Err( OmError::Generic( format!( "Error running external command {:?}", e ) ) )
+			},
+			Ok( output ) => {
+				let stdout = String::from_utf8_lossy(&output.stdout);
+				let stderr = String::from_utf8_lossy(&output.stderr);
+
+				log.add( &format!( "stdout:\n{}", stdout ) );
+				log.add( &format!( "stderr:\n{}", stderr ) );
+				log.add( &format!( "return code: {}", output.status.code().unwrap_or(-255) ) );
+				log.stdout = stdout.to_string();
+				log.stderr = stderr.to_string();
+				log.exit_code = output.status.code();
+
+				if !output.status.success() {
+					return match output.status.code() {
+						Some( code ) => Err( OmError::Generic( format!( "{} exited with {}", tool_run.tool, code ) ) ),
+						None => Err( OmError::Generic( format!( "{} was killed by a signal", tool_run.tool ) ) ),
+					};
+				}
+
+				let number_of_assets_updated = 1;
+				Ok( number_of_assets_updated )
+			},
+		}
+	}
+
+
+	// all asset configs below the content directory
+	pub fn config_files(
+		asset_build: &AssetBuild,
+	)
+	-> Result<Vec<String>,OmError> {
+		let mut config_files = Vec::new();
+		let config_glob = format!( "{}/**/*.asset_config.yaml", asset_build.content_directory );
+		let paths = match glob( &config_glob ) {
+			Ok( paths ) => paths,
+			Err( e ) => return Err( OmError::Generic( format!( "Error finding configs {}: {}", config_glob, e ) ) ),
+		};
+		for config_file in paths {
+			match config_file {
+				Err(_e) => return Err( OmError::Generic( "Error finding config".to_string() ) ),
+				Ok(config_file) => {
+//					println!("Config file: {:?}", config_file );
+					config_files.push( config_file.to_string_lossy().to_string() );
+				},
+			}
+		}
+		debug!("Found {:?} config files", config_files.len() );
+		Ok( config_files )
+	}
+
+	// all tool runs from all configs below the content directory, in a stable order
+	// fails if any config has errors, before anything runs
+	pub fn collect_tool_runs(
+		asset_build: &AssetBuild,
+	)
+	-> Result<Vec<ToolRun>,OmError> {
+		ConfigCheck::report( &ConfigCheck::check( asset_build )? )?;
+
+		let mut tool_runs = Vec::new();
+		let config_files = Asset::config_files( asset_build )?;
+		let defaults = AssetConfig::root_defaults( asset_build ).map_err( OmError::Generic )?;
+
+		for config_file in config_files {
+			// read yaml
+			debug!("===\n{:?}", config_file );
+			let mut config = String::new();
+			if let Err( e ) = File::open( &config_file ).and_then( |mut f| f.read_to_string( &mut config ) ) {
+				return Err( OmError::Generic( format!( "Error reading config {:?}: {}", config_file, e ) ) );
+			}
+			let yaml = match YamlLoader::load_from_str(&config) {
+				Ok( yaml ) => yaml,
+				Err( e ) => return Err( OmError::Generic( format!( "Error parsing config {:?}: {}", config_file, e ) ) ),
+			};
+
+			let config_file_path = Path::new(&config_file);
+			let asset_path = config_file_path.parent().unwrap_or( Path::new(".") );
+			debug!("Asset Path {:?}", asset_path );
+			// parse yaml
+//			println!("YAML: {:?}", yaml );
+			for ( doc_index, doc ) in yaml.iter().enumerate() {
+//				println!("---");
+				if doc.is_null() || doc.is_badvalue() {
+					continue;	// empty documents don't get the defaults either
+				}
+				let doc = AssetConfig::resolve( &defaults, doc, &config_file ).map_err( OmError::Generic )?;
+				let variables = AssetConfig::variables( asset_build, &config_file, &doc );
+				// foreach makes one tool run per matching file, with the file as the first input
+				let instances = match doc["foreach"].as_str() {
+					None => vec![ ( None, String::new(), variables ) ],
+					Some( pattern ) => {
+						let pattern = AssetConfig::input_path( &asset_path.to_string_lossy(), &variables, pattern )?;
+						AssetConfig::foreach_files( &pattern ).into_iter().map( |( file, relpath )| {
+							let mut variables = variables.clone();
+							AssetConfig::set_foreach_variables( &mut variables, &relpath );
+							( Some( file ), relpath, variables )
+						}).collect()
+					},
+				};
+				for ( foreach_file, foreach_input, variables ) in instances {
+					let tool = doc["tool"].as_str().unwrap_or("");
+					let command = doc["command"].as_str().unwrap_or("");
+					// "${input.dir}/name" is "./name" at the top of a foreach, but the build db and the paklist need plain names
+					let output = match variables.expand( doc["output"].as_str().unwrap_or("") )? {
+						output if output.is_empty() => output,
+						output => Paths::normalize( &output ),
+					};
+					let mut input = Vec::new();
+
+					if doc["input"].is_array() {
+						match doc["input"].as_vec() {
+							None => {},
+							Some(i) => {
+//							println!("i: {:?}", i );
+								for i in i {
+									match i.as_str() {
+										None => {},
+										Some(s) => input.push( s.to_string() )
+									}
+								}
+							},
+						}
+					} else {
+						if let Some(i) = doc["input"].as_str() {
+							input.push( i.to_string() );
+						};
+					}
+
+//				println!("INPUT {:?}", input );
+					// inputs are relative to the config, unless they start with a variable like ${data_directory}
+					let input = foreach_file.iter().cloned().map( Ok ).chain( input.iter().map( |i| {
+						AssetConfig::input_path( &asset_path.to_string_lossy(), &variables, i )
+					})).collect::<Result<Vec<_>,OmError>>()?;
+//				println!("INPUT {:?}", input );
+//				return Ok(1);
+
+//				let input = doc["input"].as_str();
+
+					let mut parameters = HashMap::new();
+
+					match doc["parameters"].as_hash() {
+						None => {},
+						Some(params) => {
+							for (name, value) in params {
+//							println!("name: {:?} -> {:?}", name, value );
+								let value = match ParameterValue::from_yaml( value ) {
+									Ok( v ) => v,
+									Err( e ) => {
+										warn!("Unhandled parameter value {}", e );
+										ParameterValue::NoValue
+									}
+								};
+								let name = match name.as_str() {
+									Some( s ) => s.to_string(),
+									x => { warn!("Unhandled name type {:?}", x ); "".to_string() },
+								};
+								parameters.insert( name, value );
+							}
+						}
+					};
+
+					// the cmd_line is kept as it is, placeholders and variables are filled in per argument when the tool runs
+					let cmd_line = doc["cmd_line"].as_str().unwrap_or("");
+					let cmd_line_variables = CmdLine::placeholders( cmd_line ).into_iter().filter( |name| {
+						!CmdLine::is_placeholder( name ) && !parameters.contains_key( name )
+					}).filter_map( |name| variables.get( &name ).map( |v| ( name, v ) ) ).collect();
+/*
+					println!("tool      : {:?}", tool );
+					println!("command.  : {:?}", command );
+					println!("output    : {:?}", output );
+					println!("input     : {:?}", input );
+					println!("parameters: {:?}", parameters );
+*/
+					let mut tool_run = ToolRun::new( tool, command, &output, &input, &parameters, cmd_line );
+					tool_run.set_variables( &cmd_line_variables );
+					tool_run.set_shell( doc["shell"].as_bool().unwrap_or( false ) );
+					tool_run.set_source( &config_file, doc_index );
+					tool_run.set_foreach_input( &foreach_input );
+
+					match tool {
+						""		=> continue,
+						"noop"	=> {
+							debug!("NOOP -> Do nothing");
+							continue;
+						},
+						_		=> tool_runs.push( tool_run ),
+					}
+				}
+			}
+		}
+
+		Ok( tool_runs )
+	}
+
+	// runs a single tool, unless the build db says it is up to date
+	fn run_tool(
+		asset_build: &AssetBuild,
+		vfs: &Vfs,
+		build_db: &Mutex<BuildDb>,
+		tool_run: &ToolRun,
+	)
+	-> RunResult {
+		if !asset_build.force {
+			let record = BuildRecord::create( asset_build, tool_run, &[] );
+			match build_db.lock().unwrap().dirty_reason( tool_run, &record ) {
+				None => return RunResult::UpToDate,
+				Some( reason ) => debug!("📁 🔧 Running {}, {}", tool_run.source(), reason ),
+			}
+		}
+
+		let start = Instant::now();
+		let mut log = RunLog::new( &asset_build.temp_directory, tool_run );
+		let result = match tool_run.tool.as_ref() {
+			"$asset"	=> {
+				trace!("$asset command found");
+				Asset::tool_asset( asset_build, vfs, tool_run, &mut log )
+			}
+			_tool		=> {
+				Asset::tool_call_external( asset_build, tool_run, &mut log )
+			},
+		};
+		log.duration = start.elapsed().as_secs_f64();
+		match result {
+			Ok( n ) => RunResult::Done( n, BuildRecord::create( asset_build, tool_run, &log.written ), log ),
+			Err( e ) => RunResult::Failed( e, log ),
+		}
+	}
+
+	pub fn build (
+		asset_build: &AssetBuild,
+	)
+	-> Result<u32,OmError> {
+		let tool_runs = Asset::collect_tool_runs( asset_build )?;
+		Asset::build_tool_runs( asset_build, &tool_runs, None )
+	}
+
+	// only builds the selected runs and everything depending on them, or everything for None
+	// the paklist and archive always cover all tool runs
+	pub fn build_tool_runs(
+		asset_build: &AssetBuild,
+		all_tool_runs: &[ToolRun],
+		selected: Option<&[usize]>,
+	)
+	-> Result<u32,OmError> {
+		let tool_runs = match selected {
+			None => all_tool_runs.to_vec(),
+			Some( selected ) => {
+				let graph = BuildGraph::create( asset_build, all_tool_runs )?;
+				let mut affected = BTreeSet::new();
+				let mut pending = selected.to_vec();
+				while let Some( i ) = pending.pop() {
+					if affected.insert( i ) {
+						pending.extend( &graph.dependents[ i ] );
+					}
+				}
+				affected.into_iter().map( |i| all_tool_runs[ i ].clone() ).collect()
+			},
+		};
+		let graph = BuildGraph::create( asset_build, &tool_runs )?;
+
+		let mut vfs = Vfs::new();
+		vfs.mount_directory( &asset_build.content_directory )?;
+		for m in &asset_build.mounts {
+			vfs.mount( m )?;
+		}
+
+		let start = Instant::now();
+		let build_db = Mutex::new( BuildDb::load( &asset_build.temp_directory ) );
+		// indexed like tool_runs, the sink sees them in the order they finish
+		let mut run_reports: Vec<Option<RunReport>> = tool_runs.iter().map( |_| None ).collect();
+		let mut number_of_assets_updated = 0;
+		let mut number_of_runs_skipped = 0;
+		let mut failures = Vec::new();
+		let mut number_of_runs_not_run = 0;
+
+		let scheduled = graph.run(
+			asset_build.jobs,
+			asset_build.keep_going,
+			|i| Asset::run_tool( asset_build, &vfs, &build_db, &tool_runs[ i ] ),
+			|i, result| {
+				let tool_run = &tool_runs[ i ];
+				match result {
+					None => {
+						debug!("📁 🔧 Not running {}, because of failures", tool_run.source() );
+						run_reports[ i ] = Some( RunReport::new( tool_run, RunStatus::NotRun ) );
+						number_of_runs_not_run += 1;
+						false
+					},
+					Some( RunResult::UpToDate ) => {
+						debug!("📁 🔧 Up to date {}", tool_run.source() );
+						let mut run_report = RunReport::new( tool_run, RunStatus::UpToDate );
+						if let Some( record ) = build_db.lock().unwrap().records.get( &BuildDb::key( tool_run ) ) {
+							run_report.set_outputs( record.produced_files() );
+						}
+						run_reports[ i ] = Some( run_report );
+						number_of_runs_skipped += 1;
+						true
+					},
+					Some( RunResult::Done( n, record, log ) ) => {
+						if let Err( e ) = log.save() {
+							warn!("{}", e );
+						}
+						info!("📁 🔧 ✅ {}", log.title );
+						debug!("{}", log.text );
+						number_of_assets_updated += n;
+						let mut run_report = RunReport::new( tool_run, RunStatus::Built );
+						run_report.set_log( &log );
+						run_report.set_outputs( record.produced_files() );
+						run_reports[ i ] = Some( run_report );
+						build_db.lock().unwrap().record( tool_run, record );
+						true
+					},
+					Some( RunResult::Failed( e, log ) ) => {
+						let _ = log.save();
+						error!("📁 🔧 ‼️ {}: {}, see {}", log.title, e, log.filename );
+						debug!("{}", log.text );
+						build_db.lock().unwrap().forget( tool_run );
+						let mut run_report = RunReport::new( tool_run, RunStatus::Failed );
+						run_report.set_log( &log );
+						run_report.set_outputs( log.written.clone() );
+						run_report.set_error( &e );
+						run_reports[ i ] = Some( run_report );
+						failures.push( ( i, e, log ) );
+						false
+					},
+				}
+			}
+		);
+
+		// the sink never sees runs that panicked
+		let panicked = ( 0..tool_runs.len() ).filter( |i| run_reports[ *i ].is_none() ).collect::<Vec<_>>();
+		for i in &panicked {
+			let mut run_report = RunReport::new( &tool_runs[ *i ], RunStatus::Failed );
+			if let Err( e ) = &scheduled {
+				run_report.set_error( e );
+			}
+			run_reports[ *i ] = Some( run_report );
+			build_db.lock().unwrap().forget( &tool_runs[ *i ] );
+		}
+
+		let build_db = build_db.into_inner().unwrap();
+		build_db.save()?;
+		if number_of_runs_skipped > 0 {
+			info!("📁 {} tool runs up to date", number_of_runs_skipped );
+		}
+
+		if !asset_build.reports.is_empty() {
+			let mut build_report = BuildReport::new();
+			for r in run_reports.into_iter().flatten() {
+				build_report.add( r );
+			}
+			build_report.set_duration( start.elapsed().as_secs_f64() );
+			for filename in &asset_build.reports {
+				build_report.save( filename )?;
+				info!("📁 📝 {} tool runs reported in {:?}", build_report.runs.len(), filename );
+			}
+		}
+
+		if failures.is_empty() && scheduled.is_ok() {
+			// includes the outputs of runs that were up to date, the build db remembers them
+			// archives from $asset pack are outputs of the build too, but are never packed into the final archive again
+			let ( archives, records ): ( Vec<_>, Vec<_> ) = all_tool_runs.iter().filter_map( |t| build_db.records.get( &BuildDb::key( t ) ) ).partition( |r| {
+				r.tool == "$asset" && r.command == "pack"
+			});
+			let data_files = |records: Vec<&BuildRecord>| {
+				let mut names = records.iter().flat_map( |r| r.data_files( &asset_build.data_directory ) ).collect::<Vec<_>>();
+				names.sort();
+				names.dedup();
+				names
+			};
+			let names = data_files( records );
+			let archives = data_files( archives );
+			Asset::write_paklist_and_archive( asset_build, &names, &archives )?;
+			return Ok( number_of_assets_updated );
+		}
+
+		let mut report = format!( "{} tool runs failed", failures.len() + panicked.len() );
+		if number_of_runs_not_run > 0 {
+			report += &format!( ", {} not run", number_of_runs_not_run );
+		}
+		for ( i, e, log ) in &failures {
+			let tool_run = &tool_runs[ *i ];
+			report += &format!( "\n{}: {}", tool_run.source(), e );
+			if !log.cmd_line.is_empty() {
+				report += &format!( "\n\tcmd_line: {}", log.cmd_line.trim() );
+			}
+			for line in log.stderr.lines() {
+				report += &format!( "\n\tstderr: {}", line );
+			}
+		}
+		for i in &panicked {
+			report += &format!( "\n{}: panicked", tool_runs[ *i ].source() );
+		}
+		if let Err( e ) = scheduled {
+			report += &format!( "\n{}", e );
+		}
+		Err( OmError::Generic( report ) )
+	}
+
+	// removes files that exist, or only lists them for a dry run
+	fn remove_files(
+		files: &[String],
+		dry_run: bool,
+	)
+	-> Result<u32,OmError> {
+		let mut number_of_files = 0;
+		for f in files.iter().filter( |f| Path::new( f ).is_file() ) {
+			if dry_run {
+				info!("📁 🧹 Would remove {}", f );
+			} else {
+				if let Err( e ) = fs::remove_file( f ) {
+					return Err( OmError::Generic( format!( "Error removing {}: {}", f, e ) ) );
+				}
+				debug!("📁 🧹 Removed {}", f );
+			}
+			number_of_files += 1;
+		}
+		Ok( number_of_files )
+	}
+
+	// removes everything the build db knows was produced, including the logs and the build db itself
+	pub fn clean(
+		asset_build: &AssetBuild,
+		dry_run: bool,
+	)
+	-> Result<u32,OmError> {
+		let build_db = BuildDb::load( &asset_build.temp_directory );
+		let directories = [ &asset_build.data_directory, &asset_build.temp_directory ];
+
+		let mut files = Vec::new();
+		for ( key, record ) in &build_db.records {
+			for d in &directories {
+				files.extend( record.data_files( d ).iter().map( |f| Paths::join( d, f ) ) );
+			}
+			files.push( RunLog::filename_for( &asset_build.temp_directory, key ) );
+		}
+		files.sort();
+		files.dedup();
+		files.push( build_db.filename().to_string() );
+
+		Asset::remove_files( &files, dry_run )
+	}
+
+	// removes the outputs of build db records whose config document no longer exists, and forgets them
+	// outputs that a remaining record also produced are kept
+	pub fn prune(
+		asset_build: &AssetBuild,
+		dry_run: bool,
+	)
+	-> Result<u32,OmError> {
+		let tool_runs = Asset::collect_tool_runs( asset_build )?;
+		let current = tool_runs.iter().map( BuildDb::key ).collect::<BTreeSet<_>>();
+		let mut build_db = BuildDb::load( &asset_build.temp_directory );
+
+		let ( live, gone ): ( Vec<_>, Vec<_> ) = build_db.records.iter().partition( |( key, _ )| current.contains( *key ) );
+		let live_files = live.iter().flat_map( |( _, r )| r.produced_files() ).map( |f| Paths::normalize( &f ) ).collect::<BTreeSet<_>>();
+		let gone = gone.into_iter().map( |( key, _ )| key.clone() ).collect::<Vec<_>>();
+
+		let mut files = Vec::new();
+		for key in &gone {
+			debug!("📁 🧹 {} no longer exists", key );
+			files.extend( build_db.records[ key ].produced_files().into_iter().filter( |f| !live_files.contains( &Paths::normalize( f ) ) ) );
+			files.push( RunLog::filename_for( &asset_build.temp_directory, key ) );
+		}
+		files.sort();
+		files.dedup();
+
+		let number_of_files = Asset::remove_files( &files, dry_run )?;
+		if !dry_run && !gone.is_empty() {
+			for key in &gone {
+				build_db.records.remove( key );
+			}
+			build_db.save()?;
+		}
+		Ok( number_of_files )
+	}
+}
@@ -0,0 +1,55 @@
+pub struct AssetBuild {
+	pub content_directory: String,
+	pub data_directory: String,
+	pub temp_directory: String,
+	pub archive: String,
+	pub paklist: String,
+	pub mounts: Vec<String>,	// searched after the content directory
+	pub reports: Vec<String>,	// build reports, junit xml for *.xml, json otherwise
+	pub force: bool,			// ignore the build db, and run everything
+	pub jobs: usize,			// number of tool runs executed at the same time
+	pub keep_going: bool,		// continue with everything not depending on a failed run
+}
+
+impl AssetBuild {
+	pub fn new(
+		content_directory: &str,
+		data_directory: &str,
+		temp_directory: &str,
+		archive: &str,
+		paklist: &str,
+	) -> AssetBuild {
+		AssetBuild {
+			content_directory: content_directory.to_string(),
+			data_directory:    data_directory.to_string(),
+			temp_directory:    temp_directory.to_string(),
+			archive:           archive.to_string(),
+			paklist:           paklist.to_string(),
+			mounts:            Vec::new(),
+			reports:           Vec::new(),
+			force:             false,
+			jobs:              1,
+			keep_going:        false,
+		}
+	}
+
+	pub fn add_mount( &mut self, mount: &str ) {
+		self.mounts.push( mount.to_string() );
+	}
+
+	pub fn add_report( &mut self, report: &str ) {
+		self.reports.push( report.to_string() );
+	}
+
+	pub fn set_force( &mut self, force: bool ) {
+		self.force = force;
+	}
+
+	pub fn set_jobs( &mut self, jobs: usize ) {
+		self.jobs = jobs.max( 1 );
+	}
+
+	pub fn set_keep_going( &mut self, keep_going: bool ) {
+		self.keep_going = keep_going;
+	}
+}
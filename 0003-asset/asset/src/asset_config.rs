@@ -0,0 +1,162 @@
+use yaml_rust::yaml::Hash;
+use yaml_rust::{Yaml, YamlLoader};
+
+use std::fs;
+use std::path::Path;
+
+use omt_core::{OmError, Paths};
+
+use crate::{AssetBuild, ToolRun, Variables};
+
+// how deep includes can be nested, deeper is most likely a cycle
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+// a document as the tool run sees it, after includes and project defaults were applied
+// the project defaults live in the root config "asset_config.yaml" in the content directory
+pub struct AssetConfig {
+}
+
+impl AssetConfig {
+	pub const ROOT: &'static str = "asset_config.yaml";
+
+	pub fn root_filename( asset_build: &AssetBuild ) -> String {
+		Paths::join( &asset_build.content_directory, AssetConfig::ROOT )
+	}
+
+	// a file with a single mapping, used for the root config and includes
+	pub fn load_mapping( filename: &str ) -> Result<Yaml,String> {
+		let config = fs::read_to_string( filename ).map_err( |e| format!( "Error reading {}: {}", filename, e ) )?;
+		let docs = YamlLoader::load_from_str( &config ).map_err( |e| format!( "Error parsing {}: {}", filename, e ) )?;
+		match docs.into_iter().next() {
+			None | Some( Yaml::Null ) => Ok( Yaml::Hash( Hash::new() ) ),
+			Some( h @ Yaml::Hash( _ ) ) => Ok( h ),
+			Some( v ) => Err( format!( "{} must contain a mapping, not {:?}", filename, v ) ),
+		}
+	}
+
+	// the project defaults, nothing if there is no root config
+	pub fn root_defaults( asset_build: &AssetBuild ) -> Result<Yaml,String> {
+		let filename = AssetConfig::root_filename( asset_build );
+		if Path::new( &filename ).is_file() {
+			let root = AssetConfig::load_mapping( &filename )?;
+			AssetConfig::apply_includes( &root, &asset_build.content_directory, 0 )
+		} else {
+			Ok( Yaml::Hash( Hash::new() ) )
+		}
+	}
+
+	// doc wins over defaults, parameters and variables are merged by name
+	pub fn merge( defaults: &Yaml, doc: &Yaml ) -> Yaml {
+		let ( mut merged, doc ) = match ( defaults, doc ) {
+			( Yaml::Hash( d ), Yaml::Hash( h ) ) => ( d.clone(), h ),
+			_ => return doc.clone(),
+		};
+		for ( k, v ) in doc {
+			let value = match ( k.as_str(), merged.get( k ), v ) {
+				( Some( "parameters" ), Some( d @ Yaml::Hash( _ ) ), Yaml::Hash( _ ) )
+				| ( Some( "variables" ), Some( d @ Yaml::Hash( _ ) ), Yaml::Hash( _ ) ) => AssetConfig::merge( d, v ),
+				_ => v.clone(),
+			};
+			merged.insert( k.clone(), value );
+		}
+		Yaml::Hash( merged )
+	}
+
+	// includes are relative to the file including them, later includes win over earlier ones
+	fn apply_includes( doc: &Yaml, directory: &str, depth: usize ) -> Result<Yaml,String> {
+		let includes = match &doc[ "include" ] {
+			Yaml::BadValue => return Ok( doc.clone() ),
+			Yaml::String( s ) => vec![ s.clone() ],
+			Yaml::Array( a ) => a.iter().map( |i| match i.as_str() {
+				Some( s ) => Ok( s.to_string() ),
+				None => Err( format!( "include must be a string, not {:?}", i ) ),
+			}).collect::<Result<Vec<_>,_>>()?,
+			v => return Err( format!( "include must be a string or a list of strings, not {:?}", v ) ),
+		};
+		if depth >= MAX_INCLUDE_DEPTH {
+			return Err( format!( "Includes nested deeper than {}, is there a cycle? {}", MAX_INCLUDE_DEPTH, includes.join( ", " ) ) );
+		}
+		let mut defaults = Yaml::Hash( Hash::new() );
+		for include in includes {
+			let filename = Paths::join( directory, &include );
+			let included = AssetConfig::load_mapping( &filename )?;
+			let included_directory = Path::new( &filename ).parent().map( |p| p.to_string_lossy().to_string() ).unwrap_or_default();
+			let included = AssetConfig::apply_includes( &included, &included_directory, depth + 1 )?;
+			defaults = AssetConfig::merge( &defaults, &included );
+		}
+		let mut merged = AssetConfig::merge( &defaults, doc );
+		if let Yaml::Hash( h ) = &mut merged {
+			h.remove( &Yaml::String( "include".to_string() ) );
+		}
+		Ok( merged )
+	}
+
+	// includes first, then the project defaults below everything
+	pub fn resolve( defaults: &Yaml, doc: &Yaml, config_file: &str ) -> Result<Yaml,String> {
+		let directory = Path::new( config_file ).parent().map( |p| p.to_string_lossy().to_string() ).unwrap_or_default();
+		let doc = AssetConfig::apply_includes( doc, &directory, 0 )?;
+		Ok( AssetConfig::merge( defaults, &doc ) )
+	}
+
+	// inputs are relative to the config, unless they start with a variable like ${data_directory}
+	pub fn input_path( asset_path: &str, variables: &Variables, input: &str ) -> Result<String,OmError> {
+		let expanded = variables.expand( input )?;
+		if input.starts_with( "${" ) || expanded.starts_with( '/' ) {
+			Ok( expanded )
+		} else {
+			Ok( Paths::join( asset_path, &expanded ) )
+		}
+	}
+
+	// the existing files for a foreach, each with its name relative to the pattern
+	// "sounds/**/*.wav" gives "sfx/boom.wav" for "sounds/sfx/boom.wav"
+	pub fn foreach_files( pattern: &str ) -> Vec<( String, String )> {
+		let base = if Path::new( pattern ).is_dir() {
+			pattern.trim_end_matches( '/' ).to_string()
+		} else if ToolRun::is_pattern( pattern ) {
+			pattern.split( '/' ).take_while( |c| !ToolRun::is_pattern( c ) ).collect::<Vec<_>>().join( "/" )
+		} else {
+			Path::new( pattern ).parent().map( |p| p.to_string_lossy().to_string() ).unwrap_or_default()
+		};
+		ToolRun::expand_inputs( &[ pattern.to_string() ] ).into_iter().filter( |f| Path::new( f ).is_file() ).map( |f| {
+			let relpath = Paths::relative( Path::new( &f ), Path::new( &base ) ).unwrap_or_else( || f.clone() );
+			( f, relpath )
+		}).collect()
+	}
+
+	// ${input.relpath}, ${input.dir}, ${input.stem} and ${input.ext} for a foreach file
+	// input.dir is "." for files directly matched by the pattern, so "${input.dir}/${input.stem}.ogg" always works
+	pub fn set_foreach_variables( variables: &mut Variables, relpath: &str ) {
+		let path = Path::new( relpath );
+		let dir = path.parent().map( |p| p.to_string_lossy().to_string() ).unwrap_or_default();
+		variables.set( "input.relpath", relpath );
+		variables.set( "input.dir", if dir.is_empty() { "." } else { &dir } );
+		variables.set( "input.stem", &path.file_stem().map( |s| s.to_string_lossy().to_string() ).unwrap_or_default() );
+		variables.set( "input.ext", &path.extension().map( |s| s.to_string_lossy().to_string() ).unwrap_or_default() );
+	}
+
+	// the builtin variables, and everything from "variables:"
+	pub fn variables( asset_build: &AssetBuild, config_file: &str, doc: &Yaml ) -> Variables {
+		let asset_path = Path::new( config_file ).parent().map( |p| p.to_string_lossy().to_string() ).unwrap_or_default();
+		let mut variables = Variables::new();
+		if let Some( h ) = doc[ "variables" ].as_hash() {
+			for ( k, v ) in h {
+				let value = match v {
+					Yaml::String( s ) => s.clone(),
+					Yaml::Integer( i ) => i.to_string(),
+					Yaml::Real( r ) => r.clone(),
+					Yaml::Boolean( b ) => b.to_string(),
+					_ => continue,
+				};
+				if let Some( k ) = k.as_str() {
+					variables.set( k, &value );
+				}
+			}
+		}
+		variables.set( "content_directory", &asset_build.content_directory );
+		variables.set( "data_directory", &asset_build.data_directory );
+		variables.set( "temp_directory", &asset_build.temp_directory );
+		variables.set( "asset_path", &asset_path );
+		variables
+	}
+}
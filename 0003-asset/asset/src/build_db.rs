@@ -0,0 +1,201 @@
+use crc::crc32;
+use log::{debug, warn};
+use omt_core::{OmError, Paths};
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{AssetBuild, ToolRun};
+
+const BUILD_DB_NAME: &str = "asset_build_db.json";
+
+// everything that decides if a tool run has to be repeated
+#[derive(Debug,Default,Clone,PartialEq,Serialize,Deserialize)]
+pub struct BuildRecord {
+	pub tool: String,
+	pub command: String,
+	#[serde(default)]
+	pub output: String,		// as declared, relative to the data directory
+	#[serde(default)]
+	pub shell: bool,
+	pub parameters: BTreeMap<String,String>,
+	pub cmd_line: String,
+	#[serde(default)]
+	pub variables: BTreeMap<String,String>,	// the values of the variables in cmd_line
+	pub inputs: BTreeMap<String,String>,	// filename -> crc32 of the content
+	pub outputs: BTreeMap<String,String>,	// filename -> crc32 of the content
+}
+
+// lives in the temp directory, one record per tool run, keyed by config file and document index
+#[derive(Debug,Default,Serialize,Deserialize)]
+pub struct BuildDb {
+	#[serde(skip)]
+	filename: String,
+	pub records: BTreeMap<String,BuildRecord>,
+}
+
+fn hash_file( filename: &str ) -> Option<String> {
+	fs::read( filename ).ok().map( |data| format!( "{:#010X}", crc32::checksum_ieee( &data ) ) )
+}
+
+impl BuildRecord {
+	// outputs are always relative to the data directory
+	pub fn output_files( asset_build: &AssetBuild, tool_run: &ToolRun ) -> Vec<String> {
+		if tool_run.output.is_empty() {
+			Vec::new()
+		} else {
+			vec![ Paths::join( &asset_build.data_directory, &tool_run.output ) ]
+		}
+	}
+
+	// written are the files the run reported, on top of the declared output
+	pub fn create( asset_build: &AssetBuild, tool_run: &ToolRun, written: &[String] ) -> BuildRecord {
+		let hashes = |files: Vec<String>| -> BTreeMap<String,String> {
+			files.into_iter().map( |f| {
+				let h = hash_file( &f ).unwrap_or_default();
+				( f, h )
+			}).collect()
+		};
+		BuildRecord {
+			tool: tool_run.tool.clone(),
+			command: tool_run.command.clone(),
+			output: tool_run.output.clone(),
+			shell: tool_run.shell,
+			parameters: tool_run.parameters.iter().map( |( k, v )| ( k.clone(), v.to_string() ) ).collect(),
+			cmd_line: tool_run.cmd_line.clone(),
+			variables: tool_run.variables.clone(),
+			inputs: hashes( tool_run.input_files() ),
+			outputs: hashes( BuildRecord::output_files( asset_build, tool_run ).into_iter().chain( written.iter().cloned() ).collect() ),
+		}
+	}
+
+	// all outputs that existed after the run
+	pub fn produced_files( &self ) -> Vec<String> {
+		self.outputs.iter().filter( |( _, h )| !h.is_empty() ).map( |( f, _ )| f.clone() ).collect()
+	}
+
+	// all outputs that existed after the run, by name relative to the data directory
+	pub fn data_files( &self, data_directory: &str ) -> Vec<String> {
+		let data_directory = Paths::normalize( data_directory );
+		self.outputs.iter().filter( |( _, h )| !h.is_empty() ).filter_map( |( f, _ )| {
+			let f = Paths::normalize( f );
+			if data_directory == "." {
+				// normalized names relative to "." don't start with it anymore
+				if f.starts_with( '/' ) || f == ".." || f.starts_with( "../" ) { None } else { Some( f ) }
+			} else {
+				Paths::relative( Path::new( &f ), Path::new( &data_directory ) )
+			}
+		}).collect()
+	}
+
+	// outputs like "atlas-%d" only name the files the tool writes, they never exist themselves
+	pub fn is_pattern( output: &str ) -> bool {
+		output.contains( "%d" )
+	}
+
+	// every output has to be there, as it was after the run
+	fn outputs_unchanged( &self ) -> bool {
+		self.outputs.iter().all( |( f, h )| {
+			if h.is_empty() {
+				BuildRecord::is_pattern( f )
+			} else {
+				hash_file( f ).as_ref() == Some( h )
+			}
+		})
+	}
+}
+
+impl BuildDb {
+	pub fn key( tool_run: &ToolRun ) -> String {
+		if tool_run.foreach_input.is_empty() {
+			format!( "{}#{}", tool_run.config_file, tool_run.doc_index )
+		} else {
+			format!( "{}#{}#{}", tool_run.config_file, tool_run.doc_index, tool_run.foreach_input )
+		}
+	}
+
+	pub fn filename( &self ) -> &str {
+		&self.filename
+	}
+
+	pub fn load( temp_directory: &str ) -> BuildDb {
+		let filename = Paths::join( temp_directory, BUILD_DB_NAME );
+		let mut db = match fs::read( &filename ) {
+			Err( _e ) => {
+				debug!("No build db found at {:?}, building everything", filename );
+				BuildDb::default()
+			},
+			Ok( data ) => match serde_json::from_slice::<BuildDb>( &data ) {
+				Ok( db ) => db,
+				Err( e ) => {
+					warn!("Ignoring broken build db {:?}: {}", filename, e );
+					BuildDb::default()
+				},
+			},
+		};
+		db.filename = filename;
+		db
+	}
+
+	pub fn save( &self ) -> Result<u32,OmError> {
+		if let Some( parent ) = Path::new( &self.filename ).parent() {
+			let _ = fs::create_dir_all( parent );
+		}
+		let data = match serde_json::to_vec_pretty( self ) {
+			Ok( d ) => d,
+			Err( _e ) => return Err( OmError::Generic( "Error writing build db".to_string() ) ),
+		};
+		match fs::write( &self.filename, data ) {
+			Ok( _ ) => Ok( self.records.len() as u32 ),
+			Err( e ) => Err( OmError::Generic( format!( "Error writing build db {}: {}", self.filename, e ) ) ),
+		}
+	}
+
+	// None if the run is up to date, otherwise the reason why it has to run
+	pub fn dirty_reason( &self, tool_run: &ToolRun, new: &BuildRecord ) -> Option<String> {
+		let old = match self.records.get( &BuildDb::key( tool_run ) ) {
+			None => return Some( "never built".to_string() ),
+			Some( r ) => r,
+		};
+		if old.tool != new.tool || old.command != new.command {
+			return Some( "tool changed".to_string() );
+		}
+		if old.output != new.output {
+			return Some( "output renamed".to_string() );
+		}
+		if old.parameters != new.parameters {
+			return Some( "parameters changed".to_string() );
+		}
+		if old.cmd_line != new.cmd_line || old.shell != new.shell {
+			return Some( "cmd_line changed".to_string() );
+		}
+		if old.variables != new.variables {
+			return Some( "variables changed".to_string() );
+		}
+		if old.inputs != new.inputs {
+			// patterns and directories can gain and lose files
+			if let Some( f ) = new.inputs.keys().find( |f| !old.inputs.contains_key( *f ) ) {
+				return Some( format!( "input added {}", f ) );
+			}
+			if let Some( f ) = old.inputs.keys().find( |f| !new.inputs.contains_key( *f ) ) {
+				return Some( format!( "input removed {}", f ) );
+			}
+			let changed = new.inputs.iter().find( |( f, h )| old.inputs.get( *f ) != Some( h ) ).map( |( f, _ )| f.clone() );
+			return Some( format!( "input changed {}", changed.unwrap_or_default() ) );
+		}
+		if !old.outputs_unchanged() {
+			return Some( "output changed or missing".to_string() );
+		}
+		None
+	}
+
+	pub fn record( &mut self, tool_run: &ToolRun, record: BuildRecord ) {
+		self.records.insert( BuildDb::key( tool_run ), record );
+	}
+
+	pub fn forget( &mut self, tool_run: &ToolRun ) {
+		self.records.remove( &BuildDb::key( tool_run ) );
+	}
+}
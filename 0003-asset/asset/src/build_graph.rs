@@ -0,0 +1,246 @@
+use glob::{MatchOptions, Pattern};
+use omt_core::{OmError, Paths};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+use crate::{AssetBuild, BuildRecord, ToolRun};
+
+#[derive(Default)]
+struct Ready {
+	runs: BTreeSet<usize>,		// lowest index first, so the order is stable with a single job
+	running: BTreeSet<usize>,
+	done: bool,
+}
+
+// which tool runs have to wait for which others, a run depends on every run whose output matches one of its inputs
+// by name, by pattern, or by being below an input directory
+pub struct BuildGraph {
+	pub dependencies: Vec<Vec<usize>>,
+	pub dependents: Vec<Vec<usize>>,
+}
+
+impl BuildGraph {
+	pub fn create( asset_build: &AssetBuild, tool_runs: &[ToolRun] ) -> Result<BuildGraph,OmError> {
+		// normalized output -> the runs writing it, sorted so everything below a directory is one range
+		let mut outputs: BTreeMap<String,Vec<usize>> = BTreeMap::new();
+		for ( o, tool_run ) in tool_runs.iter().enumerate() {
+			for out in BuildRecord::output_files( asset_build, tool_run ) {
+				outputs.entry( Paths::normalize( &out ) ).or_default().push( o );
+			}
+		}
+		let below = |prefix: String| {
+			outputs.range( prefix.clone().. ).take_while( move |( out, _ )| out.starts_with( &prefix ) )
+		};
+		let options = MatchOptions {
+			require_literal_separator: true,
+			..MatchOptions::new()
+		};
+
+		let mut dependencies: Vec<BTreeSet<usize>> = vec![ BTreeSet::new(); tool_runs.len() ];
+		for ( i, tool_run ) in tool_runs.iter().enumerate() {
+			for input in &tool_run.input {
+				let input = Paths::normalize( input );
+				let mut found: Vec<usize> = Vec::new();
+				if let Some( runs ) = outputs.get( &input ) {
+					found.extend( runs );
+				}
+				for ( _, runs ) in below( format!( "{}/", input ) ) {
+					found.extend( runs );
+				}
+				if ToolRun::is_pattern( &input ) {
+					if let Ok( pattern ) = Pattern::new( &input ) {
+						// only outputs below the directories in front of the first wildcard can match
+						let prefix = input.split( '/' ).take_while( |c| !ToolRun::is_pattern( c ) ).map( |c| format!( "{}/", c ) ).collect::<String>();
+						for ( _, runs ) in below( prefix ).filter( |( out, _ )| pattern.matches_with( out, options ) ) {
+							found.extend( runs );
+						}
+					}
+				}
+				dependencies[ i ].extend( found.into_iter().filter( |o| *o != i ) );
+			}
+		}
+		let mut dependents = vec![ Vec::new(); tool_runs.len() ];
+		for ( i, d ) in dependencies.iter().enumerate() {
+			for o in d {
+				dependents[ *o ].push( i );
+			}
+		}
+		let dependencies = dependencies.into_iter().map( |d| d.into_iter().collect() ).collect();
+
+		let graph = BuildGraph {
+			dependencies,
+			dependents,
+		};
+		graph.check_cycles( tool_runs )?;
+		Ok( graph )
+	}
+
+	// every run after all its dependencies, lowest index first where there is a choice
+	// runs that are part of a cycle are missing
+	pub fn order( &self ) -> Vec<usize> {
+		let mut waiting: Vec<usize> = self.dependencies.iter().map( |d| d.len() ).collect();
+		let mut ready: BTreeSet<usize> = ( 0..waiting.len() ).filter( |i| waiting[ *i ] == 0 ).collect();
+		let mut order = Vec::new();
+		while let Some( i ) = ready.iter().next().cloned() {
+			ready.remove( &i );
+			order.push( i );
+			for d in &self.dependents[ i ] {
+				waiting[ *d ] -= 1;
+				if waiting[ *d ] == 0 {
+					ready.insert( *d );
+				}
+			}
+		}
+		order
+	}
+
+	fn check_cycles( &self, tool_runs: &[ToolRun] ) -> Result<(),OmError> {
+		let order = self.order();
+		if order.len() == tool_runs.len() {
+			return Ok(());
+		}
+		let cycle = ( 0..tool_runs.len() ).filter( |i| !order.contains( i ) ).map( |i| {
+			tool_runs[ i ].source()
+		}).collect::<Vec<_>>();
+		Err( OmError::Generic( format!( "Tool runs depend on each other: {}", cycle.join( ", " ) ) ) )
+	}
+
+	// runs work on up to jobs threads, every run only starts after all its dependencies finished
+	// sink is called on the calling thread, with None for runs that were skipped, and never while other threads wait for it
+	// if sink returns false the run counts as failed, and its dependents are skipped
+	// without keep_going nothing new is started after a failure, but runs already started are finished
+	// a panic in work also counts as a failed run, and is returned as an error once everything stopped
+	pub fn run<R, F, S>( &self, jobs: usize, keep_going: bool, work: F, mut sink: S ) -> Result<(),OmError>
+	where
+		R: Send,
+		F: Fn( usize ) -> R + Sync,
+		S: FnMut( usize, Option<R> ) -> bool,
+	{
+		let count = self.dependencies.len();
+		let mut waiting: Vec<usize> = self.dependencies.iter().map( |d| d.len() ).collect();
+		let ready = Mutex::new( Ready {
+			runs: ( 0..count ).filter( |i| waiting[ *i ] == 0 ).collect(),
+			running: BTreeSet::new(),
+			done: count == 0,
+		});
+		let ready_changed = Condvar::new();
+		let ( sender, receiver ) = mpsc::channel();
+
+		let mut finished = vec![ false; count ];
+		let mut number_finished = 0;
+		let mut problems = Vec::new();
+
+		thread::scope( |scope| {
+			for _ in 0..jobs.max( 1 ).min( count ) {
+				let sender = sender.clone();
+				let ( ready, ready_changed, work ) = ( &ready, &ready_changed, &work );
+				scope.spawn( move || {
+					loop {
+						let i = {
+							let mut r = ready.lock().unwrap();
+							loop {
+								if r.done {
+									return;
+								}
+								if let Some( i ) = r.runs.iter().next().cloned() {
+									r.runs.remove( &i );
+									r.running.insert( i );
+									break i;
+								}
+								r = ready_changed.wait( r ).unwrap();
+							}
+						};
+						let result = panic::catch_unwind( AssertUnwindSafe( || work( i ) ) ).map_err( |e| {
+							match ( e.downcast_ref::<&str>(), e.downcast_ref::<String>() ) {
+								( Some( s ), _ ) => s.to_string(),
+								( _, Some( s ) ) => s.clone(),
+								_ => "unknown panic".to_string(),
+							}
+						});
+						if sender.send( ( i, result ) ).is_err() {
+							return;
+						}
+					}
+				});
+			}
+			drop( sender );
+
+			while number_finished < count {
+				let ( i, result ) = match receiver.recv() {
+					Ok( ir ) => ir,
+					Err( _ ) => break,
+				};
+				finished[ i ] = true;
+				number_finished += 1;
+				ready.lock().unwrap().running.remove( &i );
+
+				// other threads keep working meanwhile, the sink can take its time
+				let succeeded = match result {
+					Ok( result ) => sink( i, Some( result ) ),
+					Err( message ) => {
+						problems.push( format!( "tool run {} panicked: {}", i, message ) );
+						false
+					},
+				};
+
+				let mut r = ready.lock().unwrap();
+				let mut skipped = BTreeSet::new();
+				if succeeded {
+					for d in &self.dependents[ i ] {
+						waiting[ *d ] -= 1;
+						if waiting[ *d ] == 0 && !finished[ *d ] {
+							r.runs.insert( *d );
+						}
+					}
+				} else if keep_going {
+					// everything depending on the failed run
+					let mut failed = vec![ i ];
+					while let Some( f ) = failed.pop() {
+						for d in &self.dependents[ f ] {
+							if !finished[ *d ] && skipped.insert( *d ) {
+								failed.push( *d );
+							}
+						}
+					}
+				} else {
+					// everything that didn't start yet
+					skipped = ( 0..count ).filter( |d| !finished[ *d ] && !r.running.contains( d ) ).collect();
+				}
+				for d in &skipped {
+					r.runs.remove( d );
+					finished[ *d ] = true;
+					number_finished += 1;
+				}
+				if number_finished >= count {
+					r.done = true;
+				}
+				drop( r );
+				ready_changed.notify_all();
+
+				for d in skipped {
+					sink( d, None );
+				}
+			}
+			ready.lock().unwrap().done = true;
+			ready_changed.notify_all();
+		});
+
+		// only happens when every worker is gone
+		let unfinished = ( 0..count ).filter( |i| !finished[ *i ] ).collect::<Vec<_>>();
+		if !unfinished.is_empty() {
+			problems.push( format!( "{} tool runs never finished", unfinished.len() ) );
+			for i in unfinished {
+				sink( i, None );
+			}
+		}
+		if problems.is_empty() {
+			Ok(())
+		} else {
+			Err( OmError::Generic( problems.join( ", " ) ) )
+		}
+	}
+}
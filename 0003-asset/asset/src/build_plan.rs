@@ -0,0 +1,148 @@
+use log::debug;
+use omt_core::OmError;
+use serde::Serialize;
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::{Asset, AssetBuild, BuildDb, BuildGraph, BuildRecord, CmdLine, ToolRun};
+
+#[derive(Debug,Serialize)]
+pub struct PlannedRun {
+	pub id: usize,
+	pub source: String,
+	pub tool: String,
+	pub command: String,
+	pub cmd_line: String,			// fully expanded, empty for $asset
+	pub inputs: Vec<String>,
+	pub outputs: Vec<String>,
+	pub dependencies: Vec<usize>,
+	pub dirty: Option<String>,		// why it would run, None if it is up to date
+}
+
+// what a build would do, and why, without running anything
+#[derive(Debug,Serialize)]
+pub struct BuildPlan {
+	pub runs: Vec<PlannedRun>,
+	pub order: Vec<usize>,			// the order a build with a single job runs them in
+}
+
+fn dot_escape( s: &str ) -> String {
+	s.replace( '\\', "\\\\" ).replace( '"', "\\\"" )
+}
+
+impl BuildPlan {
+	pub fn create( asset_build: &AssetBuild ) -> Result<BuildPlan,OmError> {
+		let tool_runs = Asset::collect_tool_runs( asset_build )?;
+		BuildPlan::create_for( asset_build, &tool_runs )
+	}
+
+	pub fn create_for( asset_build: &AssetBuild, tool_runs: &[ToolRun] ) -> Result<BuildPlan,OmError> {
+		let graph = BuildGraph::create( asset_build, tool_runs )?;
+		let build_db = BuildDb::load( &asset_build.temp_directory );
+
+		let mut runs = Vec::new();
+		for ( id, tool_run ) in tool_runs.iter().enumerate() {
+			let record = BuildRecord::create( asset_build, tool_run, &[] );
+			let dirty = if asset_build.force {
+				Some( "forced".to_string() )
+			} else {
+				build_db.dirty_reason( tool_run, &record )
+			};
+			// outputs from the last run include the ones the tool reported, like all atlas pages
+			let mut outputs = BuildRecord::output_files( asset_build, tool_run );
+			if let Some( old ) = build_db.records.get( &BuildDb::key( tool_run ) ) {
+				for o in old.outputs.keys() {
+					if !outputs.contains( o ) {
+						outputs.push( o.clone() );
+					}
+				}
+			}
+			let cmd_line = match tool_run.tool.as_ref() {
+				"$asset" => String::new(),
+				_ => CmdLine::display( tool_run, &asset_build.data_directory )?,
+			};
+			runs.push( PlannedRun {
+				id,
+				source: tool_run.source(),
+				tool: tool_run.tool.clone(),
+				command: tool_run.command.clone(),
+				cmd_line,
+				inputs: record.inputs.keys().cloned().collect(),
+				outputs,
+				dependencies: graph.dependencies[ id ].clone(),
+				dirty,
+			});
+		}
+
+		// everything depending on a run that will run has to run too
+		let order = graph.order();
+		for i in &order {
+			if runs[ *i ].dirty.is_none() {
+				let dirty_dependency = runs[ *i ].dependencies.iter().find( |d| runs[ **d ].dirty.is_some() ).cloned();
+				if let Some( d ) = dirty_dependency {
+					runs[ *i ].dirty = Some( format!( "depends on {}", runs[ d ].source ) );
+				}
+			}
+		}
+
+		Ok( BuildPlan {
+			runs,
+			order,
+		})
+	}
+
+	pub fn dirty_count( &self ) -> usize {
+		self.runs.iter().filter( |r| r.dirty.is_some() ).count()
+	}
+
+	// every run that would run, in order, with what it would call
+	// up to date runs are only logged
+	pub fn to_text( &self ) -> String {
+		let mut t = String::new();
+		for i in &self.order {
+			let r = &self.runs[ *i ];
+			if let Some( reason ) = &r.dirty {
+				let _ = writeln!( t, "Would run {} {} {}, {}", r.source, r.tool, r.command, reason );
+				if r.cmd_line.is_empty() {
+					let _ = writeln!( t, "\t{} -> {}", r.inputs.join( " " ), r.outputs.join( " " ) );
+				} else {
+					let _ = writeln!( t, "\t{}", r.cmd_line );
+				}
+			} else {
+				debug!("📁 Up to date {} {} {}", r.source, r.tool, r.command );
+			}
+		}
+		t
+	}
+
+	// input -> tool run -> output, runs that would run are red
+	pub fn to_dot( &self ) -> String {
+		let mut t = String::new();
+		let _ = writeln!( t, "digraph assets {{" );
+		let _ = writeln!( t, "\trankdir=LR;" );
+		let files = self.runs.iter().flat_map( |r| r.inputs.iter().chain( r.outputs.iter() ) ).collect::<BTreeSet<_>>();
+		for f in files {
+			let _ = writeln!( t, "\t\"{}\" [shape=note];", dot_escape( f ) );
+		}
+		for r in &self.runs {
+			let color = if r.dirty.is_some() { "red" } else { "black" };
+			let _ = writeln!( t, "\t\"run {}\" [shape=box,color={},label=\"{}\\n{} {}\"];", r.id, color, dot_escape( &r.source ), dot_escape( &r.tool ), dot_escape( &r.command ) );
+			for i in &r.inputs {
+				let _ = writeln!( t, "\t\"{}\" -> \"run {}\";", dot_escape( i ), r.id );
+			}
+			for o in &r.outputs {
+				let _ = writeln!( t, "\t\"run {}\" -> \"{}\";", r.id, dot_escape( o ) );
+			}
+		}
+		let _ = writeln!( t, "}}" );
+		t
+	}
+
+	pub fn to_json( &self ) -> Result<String,OmError> {
+		match serde_json::to_string_pretty( self ) {
+			Ok( s ) => Ok( s ),
+			Err( _e ) => Err( OmError::Generic( "Error writing build plan".to_string() ) ),
+		}
+	}
+}
@@ -0,0 +1,179 @@
+use omt_core::OmError;
+use serde::Serialize;
+
+use std::fmt::Write;
+use std::fs;
+
+use crate::{RunLog, ToolRun};
+
+#[derive(Debug,Clone,Copy,PartialEq,Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+	Built,
+	UpToDate,
+	Failed,
+	NotRun,			// skipped, because something failed before
+}
+
+#[derive(Debug,Serialize)]
+pub struct RunReport {
+	pub config_file: String,
+	pub source: String,
+	pub tool: String,
+	pub command: String,
+	pub status: RunStatus,
+	pub duration: f64,				// seconds, 0 for runs that didn't run
+	pub cmd_line: String,			// as executed, only for external tools
+	pub outputs: Vec<String>,
+	pub exit_code: Option<i32>,		// only for external tools
+	pub stdout: String,
+	pub stderr: String,
+	pub error: Option<String>,
+	pub log: String,				// the log file in the temp directory
+}
+
+// every tool run of a build, for CI
+#[derive(Debug,Default,Serialize)]
+pub struct BuildReport {
+	pub duration: f64,
+	pub built: u32,
+	pub up_to_date: u32,
+	pub failed: u32,
+	pub not_run: u32,
+	pub runs: Vec<RunReport>,
+}
+
+// also drops characters xml 1.0 doesn't allow at all
+fn xml_escape( s: &str ) -> String {
+	let mut e = String::with_capacity( s.len() );
+	for c in s.chars() {
+		match c {
+			'&' => e.push_str( "&amp;" ),
+			'<' => e.push_str( "&lt;" ),
+			'>' => e.push_str( "&gt;" ),
+			'"' => e.push_str( "&quot;" ),
+			'\'' => e.push_str( "&apos;" ),
+			'\t' | '\n' | '\r' => e.push( c ),
+			c if ( c as u32 ) < 0x20 => {},
+			c => e.push( c ),
+		}
+	}
+	e
+}
+
+impl RunReport {
+	pub fn new( tool_run: &ToolRun, status: RunStatus ) -> RunReport {
+		RunReport {
+			config_file: tool_run.config_file.clone(),
+			source: tool_run.source(),
+			tool: tool_run.tool.clone(),
+			command: tool_run.command.clone(),
+			status,
+			duration: 0.0,
+			cmd_line: String::new(),
+			outputs: Vec::new(),
+			exit_code: None,
+			stdout: String::new(),
+			stderr: String::new(),
+			error: None,
+			log: String::new(),
+		}
+	}
+
+	pub fn set_outputs( &mut self, outputs: Vec<String> ) {
+		self.outputs = outputs;
+	}
+
+	pub fn set_error( &mut self, error: &OmError ) {
+		self.error = Some( error.to_string() );
+	}
+
+	pub fn set_log( &mut self, log: &RunLog ) {
+		self.duration = log.duration;
+		self.cmd_line = log.cmd_line.clone();
+		self.exit_code = log.exit_code;
+		// $asset commands have no process, what they logged is their output
+		self.stdout = if self.tool == "$asset" { log.text.clone() } else { log.stdout.clone() };
+		self.stderr = log.stderr.clone();
+		self.log = log.filename.clone();
+	}
+}
+
+impl BuildReport {
+	pub fn new() -> BuildReport {
+		BuildReport::default()
+	}
+
+	pub fn add( &mut self, run: RunReport ) {
+		match run.status {
+			RunStatus::Built => self.built += 1,
+			RunStatus::UpToDate => self.up_to_date += 1,
+			RunStatus::Failed => self.failed += 1,
+			RunStatus::NotRun => self.not_run += 1,
+		}
+		self.runs.push( run );
+	}
+
+	pub fn set_duration( &mut self, duration: f64 ) {
+		self.duration = duration;
+	}
+
+	pub fn to_json( &self ) -> Result<String,OmError> {
+		match serde_json::to_string_pretty( self ) {
+			Ok( s ) => Ok( s ),
+			Err( _e ) => Err( OmError::Generic( "Error writing build report".to_string() ) ),
+		}
+	}
+
+	// one testcase per tool run, runs that were up to date pass, runs that were not run are skipped
+	pub fn to_junit( &self ) -> String {
+		let mut t = String::new();
+		let _ = writeln!( t, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>" );
+		let _ = writeln!( t, "<testsuites name=\"asset\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">", self.runs.len(), self.failed, self.not_run, self.duration );
+		let _ = writeln!( t, "\t<testsuite name=\"asset build\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">", self.runs.len(), self.failed, self.not_run, self.duration );
+		for r in &self.runs {
+			let _ = writeln!( t, "\t\t<testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">",
+				xml_escape( &r.config_file ),
+				xml_escape( &format!( "{} {} {}", r.source, r.tool, r.command ) ),
+				r.duration,
+			);
+			match r.status {
+				RunStatus::Failed => {
+					let message = r.error.clone().unwrap_or_default();
+					let _ = writeln!( t, "\t\t\t<failure message=\"{}\">{}</failure>", xml_escape( &message ), xml_escape( &r.cmd_line ) );
+				},
+				RunStatus::NotRun => {
+					let _ = writeln!( t, "\t\t\t<skipped message=\"not run, because of failures\"/>" );
+				},
+				RunStatus::Built | RunStatus::UpToDate => {},
+			}
+			let stdout = if r.status == RunStatus::UpToDate { "up to date" } else { &r.stdout };
+			if !stdout.is_empty() {
+				let _ = writeln!( t, "\t\t\t<system-out>{}</system-out>", xml_escape( stdout ) );
+			}
+			if !r.stderr.is_empty() {
+				let _ = writeln!( t, "\t\t\t<system-err>{}</system-err>", xml_escape( &r.stderr ) );
+			}
+			let _ = writeln!( t, "\t\t</testcase>" );
+		}
+		let _ = writeln!( t, "\t</testsuite>" );
+		let _ = writeln!( t, "</testsuites>" );
+		t
+	}
+
+	// writes junit xml for *.xml, json otherwise
+	pub fn save( &self, filename: &str ) -> Result<u32,OmError> {
+		let text = if filename.to_lowercase().ends_with( ".xml" ) {
+			self.to_junit()
+		} else {
+			self.to_json()?
+		};
+		if let Some( parent ) = std::path::Path::new( filename ).parent() {
+			let _ = fs::create_dir_all( parent );
+		}
+		match fs::write( filename, text ) {
+			Ok( _ ) => Ok( self.runs.len() as u32 ),
+			Err( e ) => Err( OmError::Generic( format!( "Error writing build report {}: {}", filename, e ) ) ),
+		}
+	}
+}
@@ -0,0 +1,291 @@
+use clap::{Arg, App, ArgMatches, SubCommand};
+use log::{debug, error, info};
+
+use omt_core::OmError;
+use packer::Pool;
+
+use crate::{Asset, AssetBuild, BuildPlan, ConfigCheck, Watch};
+
+pub struct Cli {
+}
+
+impl Cli {
+	// shared by all subcommands
+	fn directory_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+		vec![
+			Arg::with_name("content-directory")
+				.long("content-directory")
+				.value_name("CONTENT-DIRECTORY")
+				.help("Set the content directory")
+				.takes_value(true),
+			Arg::with_name("data-directory")
+				.long("data-directory")
+				.value_name("DATA-DIRECTORY")
+				.help("Set the data directory")
+				.takes_value(true),
+			Arg::with_name("temp-directory")
+				.long("temp-directory")
+				.value_name("TEMP-DIRECTORY")
+				.help("Set the temp directory")
+				.takes_value(true),
+		]
+	}
+
+	// shared by build and watch
+	fn build_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+		vec![
+			Arg::with_name("archive")
+				.long("archive")
+				.value_name("archive")
+				.help("Pack everything the build wrote to the data directory into this archive")
+				.takes_value(true),
+			Arg::with_name("paklist")
+				.long("paklist")
+				.value_name("PAKLIST")
+				.help("Write the list of everything the build wrote to the data directory to this pakelist")
+				.takes_value(true),
+			Arg::with_name("jobs")
+				.long("jobs")
+				.short("j")
+				.value_name("JOBS")
+				.help("Set the number of tool runs executed in parallel [default: number of cpus]")
+				.takes_value(true),
+			Arg::with_name("keep-going")
+				.long("keep-going")
+				.short("k")
+				.help("Keep going after a tool failed, with everything that doesn't depend on it"),
+			Arg::with_name("force")
+				.long("force")
+				.help("Run all tools, even if their inputs didn't change"),
+			Arg::with_name("mount")
+				.long("mount")
+				.value_name("MOUNT")
+				.help("Also read inputs from a directory or archive, can be given multiple times")
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1),
+			Arg::with_name("report")
+				.long("report")
+				.value_name("REPORT")
+				.help("Write a report of all tool runs, JUnit XML for *.xml, JSON otherwise, can be given multiple times")
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1),
+		]
+	}
+
+	fn asset_build( sub_matches: &ArgMatches ) -> AssetBuild {
+		let content_directory = sub_matches.value_of("content-directory").unwrap_or(".").to_string();
+		let data_directory = sub_matches.value_of("data-directory").unwrap_or(".").to_string();
+		let temp_directory = sub_matches.value_of("temp-directory").unwrap_or(".").to_string();
+		let archive = sub_matches.value_of("archive").unwrap_or("").to_string();
+		let paklist = sub_matches.value_of("paklist").unwrap_or("").to_string();
+
+		debug!("content_directory: {:?}", content_directory );
+		debug!("data_directory   : {:?}", data_directory );
+		debug!("temp_directory   : {:?}", temp_directory );
+		debug!("archive          : {:?}", archive );
+		debug!("paklist          : {:?}", paklist );
+
+		AssetBuild::new(
+			&content_directory,
+			&data_directory,
+			&temp_directory,
+			&archive,
+			&paklist,
+		)
+	}
+
+	pub fn app<'a, 'b>( name: &str ) -> App<'a, 'b> {
+		App::new( name )
+			.about("Handles assets")
+			.subcommand(SubCommand::with_name("build")
+				.args(&Cli::directory_args())
+				.args(&Cli::build_args())
+				.arg(Arg::with_name("dry-run")
+					.long("dry-run")
+					.help("Only list the tool runs that would run, with their command lines and why they have to run")
+				)
+			)
+			.subcommand(SubCommand::with_name("watch")
+				.about("Rebuilds whatever is affected by changes in the content directory, until stopped")
+				.args(&Cli::directory_args())
+				.args(&Cli::build_args())
+			)
+			.subcommand(SubCommand::with_name("graph")
+				.about("Writes the graph of inputs, tool runs, and outputs")
+				.args(&Cli::directory_args())
+				.arg(Arg::with_name("format")
+					.long("format")
+					.value_name("FORMAT")
+					.help("Set the format")
+					.possible_values(&["dot", "json"])
+					.default_value("dot")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("output")
+					.long("output")
+					.value_name("OUTPUT")
+					.help("Write the graph to this file [default: stdout]")
+					.takes_value(true)
+				)
+			)
+			.subcommand(SubCommand::with_name("check")
+				.about("Checks all asset configs, without running anything")
+				.args(&Cli::directory_args())
+			)
+			.subcommand(SubCommand::with_name("clean")
+				.about("Removes everything the build db says the build produced")
+				.args(&Cli::directory_args())
+				.arg(Arg::with_name("dry-run")
+					.long("dry-run")
+					.help("Only list the files that would be removed")
+				)
+			)
+			.subcommand(SubCommand::with_name("prune")
+				.about("Removes the outputs of config documents that no longer exist")
+				.args(&Cli::directory_args())
+				.arg(Arg::with_name("dry-run")
+					.long("dry-run")
+					.help("Only list the files that would be removed")
+				)
+			)
+	}
+
+	// returns the exit code
+	pub fn run( matches: &ArgMatches ) -> i32 {
+//		println!("{:?}", matches);
+//		println!("{:?}", matches.subcommand());
+
+		if let ( name @ ( "build" | "watch" ), Some( sub_matches ) ) = matches.subcommand() {
+			let mut asset_build = Cli::asset_build( sub_matches );
+			asset_build.set_force( sub_matches.is_present("force") );
+			asset_build.set_keep_going( sub_matches.is_present("keep-going") );
+			let jobs = match sub_matches.value_of("jobs") {
+				None => Pool::default_jobs(),
+				Some( jobs ) => match jobs.parse::<usize>() {
+					Ok( n ) if n > 0 => n,
+					x => {
+						error!("Error parsing jobs {:?} >{}<", x, jobs );
+						return -1;
+					}
+				},
+			};
+			debug!("jobs             : {:?}", jobs );
+			asset_build.set_jobs( jobs );
+			if let Some( mounts ) = sub_matches.values_of("mount") {
+				for m in mounts {
+					debug!("mount            : {:?}", m );
+					asset_build.add_mount( m );
+				}
+			}
+			if let Some( reports ) = sub_matches.values_of("report") {
+				for r in reports {
+					debug!("report           : {:?}", r );
+					asset_build.add_report( r );
+				}
+			}
+
+			if name == "build" && sub_matches.is_present("dry-run") {
+				match BuildPlan::create( &asset_build ) {
+					Ok( plan ) => {
+						print!( "{}", plan.to_text() );
+						info!("📁 🔧 {} of {} tool runs would run", plan.dirty_count(), plan.runs.len() );
+						return 0;
+					},
+					Err( e ) => {
+						error!("📁 ‼️ Error {}", e );
+						return -1;
+					},
+				}
+			}
+
+			let result = if name == "build" {
+				Asset::build( &asset_build )
+			} else {
+				Watch::run( &asset_build )
+			};
+			match result {
+				Ok( number_of_files ) => {
+						info!("📁 ✅ ~{:?} assets build", number_of_files );
+						return 0;
+					},
+				Err( e ) => {
+					error!("📁 ‼️ Error {}", e );
+					return -1;
+				},
+			}
+		}
+
+		if let ("graph", Some( sub_matches ) ) = matches.subcommand() {
+			let asset_build = Cli::asset_build( sub_matches );
+			let format = sub_matches.value_of("format").unwrap_or("dot");
+			let result = BuildPlan::create( &asset_build ).and_then( |plan| {
+				let text = match format {
+					"json" => plan.to_json()?,
+					_ => plan.to_dot(),
+				};
+				match sub_matches.value_of("output") {
+					Some( output ) => match std::fs::write( output, text ) {
+						Ok( _ ) => Ok( plan.runs.len() ),
+						Err( e ) => Err( OmError::Generic( format!( "Error writing graph {}: {}", output, e ) ) ),
+					},
+					None => {
+						print!( "{}", text );
+						Ok( plan.runs.len() )
+					},
+				}
+			});
+			match result {
+				Ok( number_of_runs ) => {
+					debug!("📁 Graph with {} tool runs", number_of_runs );
+					return 0;
+				},
+				Err( e ) => {
+					error!("📁 ‼️ Error {}", e );
+					return -1;
+				},
+			}
+		}
+
+		if let ("check", Some( sub_matches ) ) = matches.subcommand() {
+			let asset_build = Cli::asset_build( sub_matches );
+			let result = ConfigCheck::check( &asset_build ).and_then( |problems| ConfigCheck::report( &problems ) );
+			match result {
+				Ok( number_of_warnings ) => {
+					info!("📁 🔍 ✅ Asset configs are fine, {} warnings", number_of_warnings );
+					return 0;
+				},
+				Err( e ) => {
+					error!("📁 ‼️ Error {}", e );
+					return -1;
+				},
+			}
+		}
+
+		if let ( name @ ( "clean" | "prune" ), Some( sub_matches ) ) = matches.subcommand() {
+			let asset_build = Cli::asset_build( sub_matches );
+			let dry_run = sub_matches.is_present("dry-run");
+			let result = if name == "clean" {
+				Asset::clean( &asset_build, dry_run )
+			} else {
+				Asset::prune( &asset_build, dry_run )
+			};
+			match result {
+				Ok( number_of_files ) => {
+					if dry_run {
+						info!("📁 🧹 {} files would be removed", number_of_files );
+					} else {
+						info!("📁 🧹 ✅ {} files removed", number_of_files );
+					}
+					return 0;
+				},
+				Err( e ) => {
+					error!("📁 ‼️ Error {}", e );
+					return -1;
+				},
+			}
+		}
+		-1
+	}
+}
@@ -0,0 +1,172 @@
+use omt_core::{OmError, Paths};
+use regex::Regex;
+
+use crate::ToolRun;
+
+// turns a cmd_line into the arguments for the tool, without ever going through a shell
+// "${tool} --output ${output} ${input}" becomes [ tool, "--output", data_directory/output, input1, input2, ... ]
+pub struct CmdLine {
+}
+
+impl CmdLine {
+	// splits like a shell would, whitespace separates arguments unless quoted or escaped
+	// nothing else is special, so "$" and friends are passed on as they are
+	pub fn tokenize( cmd_line: &str ) -> Result<Vec<String>,OmError> {
+		let mut tokens = Vec::new();
+		let mut token = String::new();
+		let mut in_token = false;
+		let mut chars = cmd_line.chars();
+		while let Some( c ) = chars.next() {
+			match c {
+				'\'' => {
+					in_token = true;
+					loop {
+						match chars.next() {
+							Some( '\'' ) => break,
+							Some( c ) => token.push( c ),
+							None => return Err( OmError::Generic( format!( "Unmatched ' in cmd_line: {}", cmd_line ) ) ),
+						}
+					}
+				},
+				'"' => {
+					in_token = true;
+					loop {
+						match chars.next() {
+							Some( '"' ) => break,
+							Some( '\\' ) => match chars.next() {
+								Some( c ) => token.push( c ),
+								None => return Err( OmError::Generic( format!( "Trailing \\ in cmd_line: {}", cmd_line ) ) ),
+							},
+							Some( c ) => token.push( c ),
+							None => return Err( OmError::Generic( format!( "Unmatched \" in cmd_line: {}", cmd_line ) ) ),
+						}
+					}
+				},
+				'\\' => {
+					in_token = true;
+					match chars.next() {
+						Some( c ) => token.push( c ),
+						None => return Err( OmError::Generic( format!( "Trailing \\ in cmd_line: {}", cmd_line ) ) ),
+					}
+				},
+				c if c.is_whitespace() => {
+					if in_token {
+						tokens.push( std::mem::take( &mut token ) );
+						in_token = false;
+					}
+				},
+				c => {
+					in_token = true;
+					token.push( c );
+				},
+			}
+		}
+		if in_token {
+			tokens.push( token );
+		}
+		Ok( tokens )
+	}
+
+	// the names of all placeholders in a cmd_line, without separators
+	pub fn placeholders( cmd_line: &str ) -> Vec<String> {
+		let re = Regex::new(r"\$\{(.*?)\}").unwrap();
+		re.captures_iter( cmd_line ).map( |c| c[ 1 ].split( ':' ).next().unwrap_or( "" ).to_string() ).collect()
+	}
+
+	// the placeholders every cmd_line has, besides the parameters and variables
+	pub fn is_placeholder( name: &str ) -> bool {
+		let name = name.split( ':' ).next().unwrap_or( name );
+		[ "", "tool", "command", "output", "input" ].contains( &name )
+	}
+
+	// the values for a placeholder, multiple values turn one argument into multiple arguments
+	// lists give one value per item, maps one "key=value" per entry, and no value gives none
+	// "${name:SEPARATOR}" joins all values into one instead, e.g. "--sizes=${sizes:,}"
+	// the output is given below the data directory, where the build db and the paklist look for it
+	fn values( tool_run: &ToolRun, data_directory: &str, placeholder: &str ) -> Result<Vec<String>,OmError> {
+		let ( name, separator ) = match placeholder.split_once( ':' ) {
+			Some( ( name, separator ) ) => ( name, Some( separator ) ),
+			None => ( placeholder, None ),
+		};
+		let values = match name {
+			"" => Vec::new(),
+			"tool" => vec![ tool_run.tool.clone() ],
+			"command" => vec![ tool_run.command.clone() ],
+			"output" => vec![ Paths::join( data_directory, &tool_run.output ) ],
+			"input" => tool_run.input_files(),
+			param => match ( tool_run.parameters.get( param ), tool_run.variables.get( param ) ) {
+				( Some( v ), _ ) => v.to_args(),
+				( None, Some( v ) ) => vec![ v.clone() ],
+				( None, None ) => return Err( OmError::Generic( format!( "Unknown placeholder ${{{}}} in cmd_line", param ) ) ),
+			},
+		};
+		match separator {
+			Some( separator ) if !values.is_empty() => Ok( vec![ values.join( separator ) ] ),
+			_ => Ok( values ),
+		}
+	}
+
+	// expands all placeholders in every argument
+	// an argument is repeated for every value of a placeholder in it, "-i=${input}" gives "-i=a", "-i=b"
+	// and dropped if a placeholder has no value
+	pub fn expand( tokens: &[String], tool_run: &ToolRun, data_directory: &str ) -> Result<Vec<String>,OmError> {
+		let re = Regex::new(r"\$\{(.*?)\}").unwrap();
+		let mut args = Vec::new();
+		for token in tokens {
+			let mut expanded = vec![ String::new() ];
+			let mut last = 0;
+			for c in re.captures_iter( token ) {
+				let m = c.get( 0 ).unwrap();
+				let values = CmdLine::values( tool_run, data_directory, c.get( 1 ).map_or( "", |m| m.as_str() ) )?;
+				let prefix = &token[ last..m.start() ];
+				expanded = expanded.iter().flat_map( |e| {
+					values.iter().map( move |v| format!( "{}{}{}", e, prefix, v ) )
+				}).collect();
+				last = m.end();
+			}
+			for e in expanded {
+				args.push( format!( "{}{}", e, &token[ last.. ] ) );
+			}
+		}
+		Ok( args )
+	}
+
+	pub fn args( tool_run: &ToolRun, data_directory: &str ) -> Result<Vec<String>,OmError> {
+		CmdLine::expand( &CmdLine::tokenize( &tool_run.cmd_line )? , tool_run, data_directory )
+	}
+
+	// the command line as it would be executed, quoted so it can be pasted into a shell
+	pub fn display( tool_run: &ToolRun, data_directory: &str ) -> Result<String,OmError> {
+		if tool_run.shell {
+			CmdLine::shell_line( tool_run, data_directory )
+		} else {
+			Ok( CmdLine::args( tool_run, data_directory )?.iter().map( |a| CmdLine::quote( a ) ).collect::<Vec<_>>().join( " " ) )
+		}
+	}
+
+	// quotes a single argument for /bin/sh, only needed when a tool explicitly asks for a shell
+	pub fn quote( arg: &str ) -> String {
+		let safe = !arg.is_empty() && arg.chars().all( |c| c.is_ascii_alphanumeric() || "-_./=:,+%@".contains( c ) );
+		if safe {
+			arg.to_string()
+		} else {
+			format!( "'{}'", arg.replace( '\'', "'\\''" ) )
+		}
+	}
+
+	// the cmd_line with all placeholders replaced by quoted values, for running through /bin/sh
+	pub fn shell_line( tool_run: &ToolRun, data_directory: &str ) -> Result<String,OmError> {
+		let re = Regex::new(r"\$\{(.*?)\}").unwrap();
+		let mut line = String::new();
+		let mut last = 0;
+		for c in re.captures_iter( &tool_run.cmd_line ) {
+			let m = c.get( 0 ).unwrap();
+			let values = CmdLine::values( tool_run, data_directory, c.get( 1 ).map_or( "", |m| m.as_str() ) )?;
+			line.push_str( &tool_run.cmd_line[ last..m.start() ] );
+			line.push_str( &values.iter().map( |v| CmdLine::quote( v ) ).collect::<Vec<_>>().join( " " ) );
+			last = m.end();
+		}
+		line.push_str( &tool_run.cmd_line[ last.. ] );
+		Ok( line )
+	}
+}
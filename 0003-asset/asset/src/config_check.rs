@@ -0,0 +1,401 @@
+use log::{error, warn};
+use omt_core::OmError;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+use yaml_rust::{Yaml, YamlLoader};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::{Asset, AssetBuild, AssetConfig, ParameterValue, Variables};
+
+const KNOWN_KEYS: &[&str] = &[ "tool", "command", "output", "input", "parameters", "cmd_line", "shell", "include", "variables", "foreach" ];
+const ASSET_COMMANDS: &[&str] = &[ "dump", "copy", "atlas", "pack" ];
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Severity {
+	Warning,
+	Error,
+}
+
+#[derive(Debug,Clone)]
+pub struct ConfigProblem {
+	pub severity: Severity,
+	pub config_file: String,
+	pub doc_index: usize,
+	pub line: usize,	// 1 based, 0 if unknown
+	pub col: usize,		// 1 based
+	pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+	fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+		write!(f, "{}:{}:{}: document #{}: {}", self.config_file, self.line, self.col, self.doc_index, self.message )
+	}
+}
+
+enum Frame {
+	Map( String, Option<String> ),	// path, key waiting for its value
+	Seq( String, usize ),			// path, index of the next item
+	Key,							// a collection used as a map key, only skipped over
+}
+
+// where every map key and sequence item of every document starts, by path like "parameters.size" or "input[1]"
+#[derive(Default)]
+struct Positions {
+	docs: Vec<HashMap<String,Marker>>,
+	stack: Vec<Frame>,
+}
+
+impl Positions {
+	// the path of a node that starts here, None if it is a map key
+	fn begin( &mut self, key: Option<&str>, mark: Marker ) -> Option<String> {
+		let join = |path: &str, key: &str| if path.is_empty() { key.to_string() } else { format!( "{}.{}", path, key ) };
+		let doc = self.docs.last_mut()?;
+		match self.stack.last_mut() {
+			None => Some( String::new() ),
+			Some( Frame::Map( path, waiting @ None ) ) => {
+				let key = key.unwrap_or( "?" ).to_string();
+				doc.insert( join( path, &key ), mark );
+				*waiting = Some( key );
+				None
+			},
+			Some( Frame::Map( path, Some( key ) ) ) => Some( join( path, key ) ),
+			Some( Frame::Seq( path, index ) ) => {
+				let path = format!( "{}[{}]", path, index );
+				doc.insert( path.clone(), mark );
+				Some( path )
+			},
+			Some( Frame::Key ) => None,
+		}
+	}
+
+	// a value finished, the parent moves on
+	fn end( &mut self ) {
+		match self.stack.last_mut() {
+			Some( Frame::Map( _, key ) ) => *key = None,
+			Some( Frame::Seq( _, index ) ) => *index += 1,
+			_ => {},
+		}
+	}
+}
+
+impl MarkedEventReceiver for Positions {
+	fn on_event( &mut self, ev: Event, mark: Marker ) {
+		match ev {
+			Event::DocumentStart => {
+				let mut doc = HashMap::new();
+				doc.insert( String::new(), mark );	// problems with the whole document point at its start
+				self.docs.push( doc );
+				self.stack.clear();
+			},
+			Event::Scalar( .. ) | Event::Alias( _ ) => {
+				let key = match &ev {
+					Event::Scalar( v, .. ) => Some( v.as_str() ),
+					_ => None,
+				};
+				let is_value = self.begin( key, mark ).is_some();
+				if is_value {
+					self.end();
+				}
+			},
+			Event::SequenceStart( _ ) => {
+				let frame = match self.begin( None, mark ) {
+					Some( path ) => Frame::Seq( path, 0 ),
+					None => Frame::Key,
+				};
+				self.stack.push( frame );
+			},
+			Event::MappingStart( _ ) => {
+				let frame = match self.begin( None, mark ) {
+					Some( path ) => Frame::Map( path, None ),
+					None => Frame::Key,
+				};
+				self.stack.push( frame );
+			},
+			Event::SequenceEnd | Event::MappingEnd => {
+				if let Some( frame ) = self.stack.pop() {
+					if !matches!( frame, Frame::Key ) {
+						self.end();
+					}
+				}
+			},
+			_ => {},
+		}
+	}
+}
+
+// finds everything wrong with the asset configs, before any tool runs
+pub struct ConfigCheck {
+	config_file: String,
+	variables: Option<Variables>,	// None for the root config, which only has defaults
+	doc_index: usize,
+	positions: HashMap<String,Marker>,
+	problems: Vec<ConfigProblem>,
+}
+
+impl ConfigCheck {
+	// keys that came from an include point at the include, everything else at least at the document
+	fn problem( &mut self, severity: Severity, path: &str, message: String ) {
+		let position = [ path, "include", "" ].iter().find_map( |p| self.positions.get( *p ) );
+		let ( line, col ) = position.map( |m| ( m.line(), m.col() + 1 ) ).unwrap_or( ( 0, 0 ) );
+		self.problems.push( ConfigProblem {
+			severity,
+			config_file: self.config_file.clone(),
+			doc_index: self.doc_index,
+			line,
+			col,
+			message,
+		});
+	}
+
+	fn error( &mut self, path: &str, message: String ) {
+		self.problem( Severity::Error, path, message );
+	}
+
+	fn warning( &mut self, path: &str, message: String ) {
+		self.problem( Severity::Warning, path, message );
+	}
+
+	fn check_string( &mut self, doc: &Yaml, key: &str ) -> Option<String> {
+		match &doc[ key ] {
+			Yaml::BadValue => None,
+			Yaml::String( s ) => Some( s.clone() ),
+			v => {
+				self.error( key, format!( "{} must be a string, not {:?}", key, v ) );
+				None
+			},
+		}
+	}
+
+	fn check_document( &mut self, doc: &Yaml, root: bool ) {
+		let hash = match doc {
+			Yaml::Null | Yaml::BadValue => return,	// empty documents are fine, e.g. after a trailing ---
+			Yaml::Hash( hash ) => hash,
+			v => {
+				self.error( "", format!( "Document must be a mapping, not {:?}", v ) );
+				return;
+			},
+		};
+
+		for key in hash.keys() {
+			match key.as_str() {
+				Some( k ) if KNOWN_KEYS.contains( &k ) => {},
+				Some( k ) => self.error( k, format!( "Unknown key {:?}", k ) ),
+				None => self.error( "?", format!( "Key must be a string, not {:?}", key ) ),
+			}
+		}
+
+		let tool = self.check_string( doc, "tool" );
+		let command = self.check_string( doc, "command" );
+		self.check_string( doc, "output" );
+		let foreach = self.check_string( doc, "foreach" );
+		let cmd_line = self.check_string( doc, "cmd_line" );
+
+		match tool.as_deref() {
+			_ if root => {},
+			None | Some( "" ) => self.error( "", "Missing tool".to_string() ),
+			Some( "$asset" ) => {
+				match command.as_deref() {
+					Some( c ) if ASSET_COMMANDS.contains( &c ) => {},
+					Some( c ) => self.error( "command", format!( "Unknown $asset command {:?}, expected one of {}", c, ASSET_COMMANDS.join( ", " ) ) ),
+					None => self.error( "", format!( "Missing command, expected one of {}", ASSET_COMMANDS.join( ", " ) ) ),
+				}
+				// a cmd_line from the project defaults is fine, it is meant for the other tools
+				if cmd_line.map( |c| !c.is_empty() ).unwrap_or( false ) && self.positions.contains_key( "cmd_line" ) {
+					self.warning( "cmd_line", "cmd_line is ignored by $asset".to_string() );
+				}
+			},
+			Some( _ ) => {},
+		}
+
+		match &doc[ "shell" ] {
+			Yaml::BadValue | Yaml::Boolean( _ ) => {},
+			v => self.error( "shell", format!( "shell must be true or false, not {:?}", v ) ),
+		}
+
+		match &doc[ "input" ] {
+			Yaml::BadValue | Yaml::String( _ ) => {},
+			Yaml::Array( inputs ) => {
+				for ( i, input ) in inputs.iter().enumerate() {
+					if input.as_str().is_none() {
+						self.error( &format!( "input[{}]", i ), format!( "input must be a string, not {:?}", input ) );
+					}
+				}
+			},
+			v => self.error( "input", format!( "input must be a string or a list of strings, not {:?}", v ) ),
+		}
+
+		match &doc[ "parameters" ] {
+			Yaml::BadValue | Yaml::Null => {},
+			Yaml::Hash( params ) => {
+				for ( name, value ) in params {
+					let name = match name.as_str() {
+						Some( n ) => n,
+						None => {
+							self.error( "parameters.?", format!( "Parameter name must be a string, not {:?}", name ) );
+							continue;
+						},
+					};
+					if let Err( e ) = ParameterValue::from_yaml( value ) {
+						self.error( &format!( "parameters.{}", name ), format!( "Unsupported value for parameter {:?}: {}", name, e ) );
+					}
+				}
+			},
+			v => self.error( "parameters", format!( "parameters must be a mapping, not {:?}", v ) ),
+		}
+
+		match &doc[ "variables" ] {
+			Yaml::BadValue => {},
+			Yaml::Hash( variables ) => {
+				for ( name, value ) in variables {
+					match ( name.as_str(), value ) {
+						( Some( _ ), Yaml::String( _ ) | Yaml::Integer( _ ) | Yaml::Real( _ ) | Yaml::Boolean( _ ) ) => {},
+						( Some( n ), v ) => self.error( &format!( "variables.{}", n ), format!( "Variable {:?} must be a single value, not {:?}", n, v ) ),
+						( None, _ ) => self.error( "variables.?", format!( "Variable name must be a string, not {:?}", name ) ),
+					}
+				}
+			},
+			v => self.error( "variables", format!( "variables must be a mapping, not {:?}", v ) ),
+		}
+
+		if let Some( mut variables ) = self.variables.take() {
+			if let Some( foreach ) = foreach {
+				let asset_path = Path::new( &self.config_file ).parent().map( |p| p.to_string_lossy().to_string() ).unwrap_or_default();
+				match AssetConfig::input_path( &asset_path, &variables, &foreach ) {
+					Ok( pattern ) if AssetConfig::foreach_files( &pattern ).is_empty() => {
+						self.warning( "foreach", format!( "foreach {:?} matches no files", foreach ) );
+					},
+					Ok( _ ) => {},
+					Err( e ) => self.error( "foreach", e.to_string() ),
+				}
+				AssetConfig::set_foreach_variables( &mut variables, "foreach.input" );
+			}
+			if let Some( output ) = doc[ "output" ].as_str() {
+				if let Err( e ) = variables.expand( output ) {
+					self.error( "output", e.to_string() );
+				}
+			}
+			let inputs = match &doc[ "input" ] {
+				Yaml::String( i ) => vec![ ( "input".to_string(), i.clone() ) ],
+				Yaml::Array( a ) => a.iter().enumerate().filter_map( |( n, i )| i.as_str().map( |i| ( format!( "input[{}]", n ), i.to_string() ) ) ).collect(),
+				_ => Vec::new(),
+			};
+			for ( path, input ) in inputs {
+				if let Err( e ) = variables.expand( &input ) {
+					self.error( &path, e.to_string() );
+				}
+			}
+			self.variables = Some( variables );
+		}
+	}
+
+	// all problems in one config file, defaults is None when checking the root config itself
+	pub fn check_file( asset_build: &AssetBuild, config_file: &str, defaults: Option<&Yaml> ) -> Vec<ConfigProblem> {
+		let mut check = ConfigCheck {
+			config_file: config_file.to_string(),
+			variables: None,
+			doc_index: 0,
+			positions: HashMap::new(),
+			problems: Vec::new(),
+		};
+		let config = match fs::read_to_string( config_file ) {
+			Ok( c ) => c,
+			Err( e ) => {
+				check.error( "", format!( "Error reading config: {}", e ) );
+				return check.problems;
+			},
+		};
+		let parsed = YamlLoader::load_from_str( &config ).and_then( |docs| {
+			let mut positions = Positions::default();
+			Parser::new( config.chars() ).load( &mut positions, true ).map( |_| ( docs, positions ) )
+		});
+		let ( docs, positions ) = match parsed {
+			Ok( dp ) => dp,
+			Err( e ) => {
+				check.problems.push( ConfigProblem {
+					severity: Severity::Error,
+					config_file: config_file.to_string(),
+					doc_index: 0,
+					line: e.marker().line(),
+					col: e.marker().col() + 1,
+					message: format!( "Invalid yaml: {}", e ),
+				});
+				return check.problems;
+			},
+		};
+		for ( ( doc_index, doc ), positions ) in docs.iter().enumerate().zip( positions.docs ) {
+			check.doc_index = doc_index;
+			check.positions = positions;
+			let defaults = match defaults {
+				None => {
+					check.check_document( doc, true );
+					continue;
+				},
+				Some( d ) => d,
+			};
+			if doc.is_null() || doc.is_badvalue() {
+				continue;
+			}
+			match AssetConfig::resolve( defaults, doc, config_file ) {
+				Ok( resolved ) => {
+					check.variables = Some( AssetConfig::variables( asset_build, config_file, &resolved ) );
+					check.check_document( &resolved, false );
+				},
+				Err( e ) => check.error( "include", e ),
+			}
+		}
+		check.problems
+	}
+
+	// all problems in all configs below the content directory, including the root config
+	pub fn check( asset_build: &AssetBuild ) -> Result<Vec<ConfigProblem>,OmError> {
+		let root = AssetConfig::root_filename( asset_build );
+		let mut problems = Vec::new();
+		let defaults = if Path::new( &root ).is_file() {
+			problems = ConfigCheck::check_file( asset_build, &root, None );
+			match AssetConfig::root_defaults( asset_build ) {
+				Ok( d ) => d,
+				Err( e ) => {
+					if problems.is_empty() {
+						problems.push( ConfigProblem {
+							severity: Severity::Error,
+							config_file: root,
+							doc_index: 0,
+							line: 0,
+							col: 0,
+							message: e,
+						});
+					}
+					return Ok( problems );
+				},
+			}
+		} else {
+			Yaml::Hash( Default::default() )
+		};
+		for f in Asset::config_files( asset_build )? {
+			problems.append( &mut ConfigCheck::check_file( asset_build, &f, Some( &defaults ) ) );
+		}
+		Ok( problems )
+	}
+
+	// logs every problem, and fails if there are errors
+	pub fn report( problems: &[ConfigProblem] ) -> Result<u32,OmError> {
+		let mut number_of_errors = 0;
+		for p in problems {
+			match p.severity {
+				Severity::Warning => warn!("📁 🔍 {}", p ),
+				Severity::Error => {
+					error!("📁 🔍 {}", p );
+					number_of_errors += 1;
+				},
+			}
+		}
+		if number_of_errors > 0 {
+			return Err( OmError::Generic( format!( "{} errors in asset configs", number_of_errors ) ) );
+		}
+		Ok( problems.len() as u32 )
+	}
+}
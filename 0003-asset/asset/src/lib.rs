@@ -0,0 +1,41 @@
+pub mod asset;
+pub use asset::Asset;
+
+pub mod asset_config;
+pub use asset_config::AssetConfig;
+
+pub mod asset_build;
+pub use asset_build::AssetBuild;
+
+pub mod build_graph;
+pub use build_graph::BuildGraph;
+
+pub mod build_plan;
+pub use build_plan::{BuildPlan, PlannedRun};
+
+pub mod build_report;
+pub use build_report::{BuildReport, RunReport, RunStatus};
+
+pub mod build_db;
+pub use build_db::{BuildDb, BuildRecord};
+
+pub mod cli;
+pub use cli::Cli;
+
+pub mod config_check;
+pub use config_check::{ConfigCheck, ConfigProblem, Severity};
+
+pub mod cmd_line;
+pub use cmd_line::CmdLine;
+
+pub mod run_log;
+pub use run_log::RunLog;
+
+pub mod tool_run;
+pub use tool_run::{ParameterValue, ToolRun};
+
+pub mod watch;
+pub use watch::Watch;
+
+pub mod variables;
+pub use variables::Variables;
@@ -0,0 +1,62 @@
+use omt_core::{OmError, Paths};
+
+use std::fs;
+
+use crate::{BuildDb, ToolRun};
+
+// everything a single tool run has to say, collected while it runs
+// parallel runs would interleave their output otherwise, so it is only shown as one block once the run finished
+pub struct RunLog {
+	pub title: String,
+	pub filename: String,
+	pub text: String,
+	pub cmd_line: String,	// as executed, only for external tools
+	pub stdout: String,
+	pub stderr: String,
+	pub exit_code: Option<i32>,	// only for external tools, None if killed by a signal
+	pub duration: f64,		// seconds
+	pub written: Vec<String>,	// files written besides the declared output
+}
+
+impl RunLog {
+	// one log per build db key, later runs overwrite it
+	pub fn filename_for( temp_directory: &str, key: &str ) -> String {
+		let name = format!( "{}.log", key ).replace( [ '/', '\\' ], "_" ).replace( '#', "-" );
+		Paths::join( &Paths::join( temp_directory, "logs" ), &name )
+	}
+
+	pub fn new( temp_directory: &str, tool_run: &ToolRun ) -> RunLog {
+		RunLog {
+			title: format!( "{} {} {}", tool_run.source(), tool_run.tool, tool_run.command ),
+			filename: RunLog::filename_for( temp_directory, &BuildDb::key( tool_run ) ),
+			text: String::new(),
+			cmd_line: String::new(),
+			stdout: String::new(),
+			stderr: String::new(),
+			exit_code: None,
+			duration: 0.0,
+			written: Vec::new(),
+		}
+	}
+
+	pub fn add( &mut self, line: &str ) {
+		self.text.push_str( line );
+		if !line.ends_with( '\n' ) {
+			self.text.push( '\n' );
+		}
+	}
+
+	pub fn wrote( &mut self, filename: &str ) {
+		self.written.push( filename.to_string() );
+	}
+
+	pub fn save( &self ) -> Result<u32,OmError> {
+		if let Some( parent ) = std::path::Path::new( &self.filename ).parent() {
+			let _ = fs::create_dir_all( parent );
+		}
+		match fs::write( &self.filename, format!( "{}\n{}", self.title, self.text ) ) {
+			Ok( _ ) => Ok( self.text.len() as u32 ),
+			Err( e ) => Err( OmError::Generic( format!( "Error writing log {}: {}", self.filename, e ) ) ),
+		}
+	}
+}
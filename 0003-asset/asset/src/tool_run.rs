@@ -0,0 +1,282 @@
+use glob::{glob, MatchOptions, Pattern};
+use omt_core::{OmError, Paths};
+use yaml_rust::Yaml;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Clone,PartialEq,Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum ParameterValue {
+	NoValue,
+	IntegerValue(i64),
+	FloatValue(f64),
+	BoolValue(bool),
+	StringValue(String),
+	ListValue(Vec<ParameterValue>),
+	MapValue(BTreeMap<String,ParameterValue>),	// sorted, so the cmd_line and the build db are stable
+}
+
+impl fmt::Display for ParameterValue {
+	fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+		match self {
+			ParameterValue::NoValue => write!(f, "NOVALUE"),
+			ParameterValue::IntegerValue( i ) => write!(f, "{}", i),
+			ParameterValue::FloatValue( v ) => write!(f, "{:?}", v),
+			ParameterValue::BoolValue( b ) => write!(f, "{}", b),
+			ParameterValue::StringValue( s ) => write!(f, "\"{}\"", s ),
+			ParameterValue::ListValue( l ) => {
+				write!(f, "[{}]", l.iter().map( |v| v.to_string() ).collect::<Vec<_>>().join( ", " ) )
+			},
+			ParameterValue::MapValue( m ) => {
+				write!(f, "{{{}}}", m.iter().map( |( k, v )| format!( "\"{}\": {}", k, v ) ).collect::<Vec<_>>().join( ", " ) )
+			},
+		}
+//		write!(f, "FUU")
+	}
+}
+
+impl ParameterValue {
+	// everything yaml can hold, except aliases and non string keys
+	pub fn from_yaml( yaml: &Yaml ) -> Result<ParameterValue,String> {
+		match yaml {
+			Yaml::Null => Ok( ParameterValue::NoValue ),
+			Yaml::Integer( i ) => Ok( ParameterValue::IntegerValue( *i ) ),
+			Yaml::Real( r ) => match r.parse::<f64>() {
+				Ok( v ) => Ok( ParameterValue::FloatValue( v ) ),
+				Err( _e ) => Err( format!( "{:?} is not a float", r ) ),
+			},
+			Yaml::Boolean( b ) => Ok( ParameterValue::BoolValue( *b ) ),
+			Yaml::String( s ) => Ok( ParameterValue::StringValue( s.clone() ) ),
+			Yaml::Array( a ) => a.iter().map( ParameterValue::from_yaml ).collect::<Result<Vec<_>,_>>().map( ParameterValue::ListValue ),
+			Yaml::Hash( h ) => h.iter().map( |( k, v )| match k.as_str() {
+				Some( k ) => ParameterValue::from_yaml( v ).map( |v| ( k.to_string(), v ) ),
+				None => Err( format!( "map key {:?} is not a string", k ) ),
+			}).collect::<Result<BTreeMap<_,_>,_>>().map( ParameterValue::MapValue ),
+			v => Err( format!( "unsupported value {:?}", v ) ),
+		}
+	}
+
+	// the plain value, as passed to a tool on the command line
+	// lists and maps are joined with ",", map entries as "key=value"
+	pub fn to_arg(&self) -> String {
+		match self {
+			ParameterValue::NoValue => String::new(),
+			ParameterValue::IntegerValue( i ) => i.to_string(),
+			ParameterValue::FloatValue( v ) => v.to_string(),
+			ParameterValue::BoolValue( b ) => b.to_string(),
+			ParameterValue::StringValue( s ) => s.clone(),
+			ParameterValue::ListValue( _ ) | ParameterValue::MapValue( _ ) => self.to_args().join( "," ),
+		}
+	}
+
+	// one argument per list item or map entry, none for no value
+	pub fn to_args(&self) -> Vec<String> {
+		match self {
+			ParameterValue::NoValue => Vec::new(),
+			ParameterValue::ListValue( l ) => l.iter().map( |v| v.to_arg() ).collect(),
+			ParameterValue::MapValue( m ) => m.iter().map( |( k, v )| format!( "{}={}", k, v.to_arg() ) ).collect(),
+			v => vec![ v.to_arg() ],
+		}
+	}
+
+	// strings are accepted too, as long as they parse
+	pub fn as_integer(&self) -> Option<i64> {
+		match self {
+			ParameterValue::IntegerValue( i ) => Some( *i ),
+			ParameterValue::StringValue( s ) => s.parse().ok(),
+			_ => None,
+		}
+	}
+
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			ParameterValue::IntegerValue( i ) => Some( *i as f64 ),
+			ParameterValue::FloatValue( v ) => Some( *v ),
+			ParameterValue::StringValue( s ) => s.parse().ok(),
+			_ => None,
+		}
+	}
+
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			ParameterValue::BoolValue( b ) => Some( *b ),
+			ParameterValue::StringValue( s ) => s.parse().ok(),
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			ParameterValue::StringValue( s ) => Some( s ),
+			_ => None,
+		}
+	}
+
+	// a single value counts as a list with one item
+	pub fn as_list(&self) -> Vec<ParameterValue> {
+		match self {
+			ParameterValue::NoValue => Vec::new(),
+			ParameterValue::ListValue( l ) => l.clone(),
+			v => vec![ v.clone() ],
+		}
+	}
+
+	pub fn as_map(&self) -> Option<&BTreeMap<String,ParameterValue>> {
+		match self {
+			ParameterValue::MapValue( m ) => Some( m ),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct ToolRun {
+	pub tool: String,
+	pub command: String,
+	pub output: String,
+	pub input: Vec<String>,
+	pub parameters: HashMap<String,ParameterValue>,
+	pub cmd_line: String,
+	pub variables: BTreeMap<String,String>,	// the config variables cmd_line uses, filled in like the placeholders
+	pub shell: bool,			// run cmd_line through /bin/sh instead of calling the tool directly
+	pub config_file: String,	// where the run was defined, for the build db and error reporting
+	pub doc_index: usize,
+	pub foreach_input: String,	// the file this run was made for, relative to the foreach pattern, only for foreach
+}
+
+impl ToolRun {
+	pub fn new(
+		tool: &str,
+		command: &str,
+		output: &str,
+		input: &[String],
+		parameters: &HashMap<String,ParameterValue>,
+		cmd_line: &str,
+	) -> ToolRun
+	{
+		ToolRun {
+			tool: tool.to_string(),
+			command: command.to_string(),
+			output: output.to_string(),
+			input: input.to_vec(),
+			parameters: parameters.clone(),
+			cmd_line: cmd_line.to_string(),
+			variables: BTreeMap::new(),
+			shell: false,
+			config_file: String::new(),
+			doc_index: 0,
+			foreach_input: String::new(),
+		}
+	}
+
+	pub fn set_variables( &mut self, variables: &BTreeMap<String,String> ) {
+		self.variables = variables.clone();
+	}
+
+	pub fn set_shell( &mut self, shell: bool ) {
+		self.shell = shell;
+	}
+
+	pub fn set_source( &mut self, config_file: &str, doc_index: usize ) {
+		self.config_file = config_file.to_string();
+		self.doc_index = doc_index;
+	}
+
+	pub fn set_foreach_input( &mut self, foreach_input: &str ) {
+		self.foreach_input = foreach_input.to_string();
+	}
+
+	// where the run comes from, for messages
+	pub fn source( &self ) -> String {
+		if self.foreach_input.is_empty() {
+			format!( "{} #{}", self.config_file, self.doc_index )
+		} else {
+			format!( "{} #{} {}", self.config_file, self.doc_index, self.foreach_input )
+		}
+	}
+
+	pub fn is_pattern( input: &str ) -> bool {
+		input.contains( [ '*', '?', '[' ] )
+	}
+
+	// if a file is one of the inputs, directly, by pattern, or below an input directory
+	// works on names only, so it also answers for files that don't exist yet
+	pub fn input_matches( input: &str, filename: &str ) -> bool {
+		let input = Paths::normalize( input );
+		let filename = Paths::normalize( filename );
+		if filename == input || filename.starts_with( &format!( "{}/", input ) ) {
+			return true;
+		}
+		let options = MatchOptions {
+			require_literal_separator: true,
+			..MatchOptions::new()
+		};
+		ToolRun::is_pattern( &input ) && Pattern::new( &input ).map( |p| p.matches_with( &filename, options ) ).unwrap_or( false )
+	}
+
+	// inputs can be files, glob patterns, or directories, expanded in the order given, each sorted by name
+	// a missing file is kept, so the tool can complain about it, and it shows up in the build db once it exists
+	pub fn input_files( &self ) -> Vec<String> {
+		ToolRun::expand_inputs( &self.input )
+	}
+
+	pub fn expand_inputs( inputs: &[String] ) -> Vec<String> {
+		let mut files = Vec::new();
+		let mut seen = HashSet::new();
+		for i in inputs {
+			let mut matches = if Path::new( i ).is_dir() {
+				Paths::files_in_directory( Path::new( i ) ).iter().map( |f| Paths::join( i.trim_end_matches( '/' ), f ) ).collect()
+			} else if ToolRun::is_pattern( i ) {
+				match glob( i ) {
+					Ok( paths ) => paths.flatten().filter( |p| p.is_file() ).map( |p| p.to_string_lossy().to_string() ).collect(),
+					Err( _e ) => vec![ i.clone() ],
+				}
+			} else {
+				vec![ i.clone() ]
+			};
+			matches.sort();
+			for m in matches {
+				if seen.insert( m.clone() ) {
+					files.push( m );
+				}
+			}
+		}
+		files
+	}
+
+	fn parameter<T>( &self, name: &str, kind: &str, get: impl Fn( &ParameterValue ) -> Option<T> ) -> Result<Option<T>,OmError> {
+		match self.parameters.get( name ) {
+			None | Some( ParameterValue::NoValue ) => Ok( None ),
+			Some( v ) => match get( v ) {
+				Some( t ) => Ok( Some( t ) ),
+				None => Err( OmError::Generic( format!( "Parameter {} is not {}: {}", name, kind, v ) ) ),
+			},
+		}
+	}
+
+	pub fn integer_parameter( &self, name: &str, default: i64 ) -> Result<i64,OmError> {
+		Ok( self.parameter( name, "an integer", ParameterValue::as_integer )?.unwrap_or( default ) )
+	}
+
+	pub fn float_parameter( &self, name: &str, default: f64 ) -> Result<f64,OmError> {
+		Ok( self.parameter( name, "a number", ParameterValue::as_float )?.unwrap_or( default ) )
+	}
+
+	pub fn bool_parameter( &self, name: &str, default: bool ) -> Result<bool,OmError> {
+		Ok( self.parameter( name, "true or false", ParameterValue::as_bool )?.unwrap_or( default ) )
+	}
+
+	pub fn string_parameter( &self, name: &str ) -> Result<Option<String>,OmError> {
+		self.parameter( name, "a string", |v| v.as_str().map( |s| s.to_string() ) )
+	}
+
+	pub fn list_parameter( &self, name: &str ) -> Result<Vec<ParameterValue>,OmError> {
+		Ok( self.parameter( name, "a list", |v| Some( v.as_list() ) )?.unwrap_or_default() )
+	}
+
+	pub fn map_parameter( &self, name: &str ) -> Result<BTreeMap<String,ParameterValue>,OmError> {
+		Ok( self.parameter( name, "a map", |v| v.as_map().cloned() )?.unwrap_or_default() )
+	}
+}
@@ -0,0 +1,51 @@
+use omt_core::OmError;
+use regex::{Captures, Regex};
+
+use std::collections::BTreeMap;
+use std::env;
+
+// "${name}" in output, input and cmd_line
+// the cmd_line is only expanded when the tool runs, one argument at a time, see CmdLine
+// names are looked up in the set values first, then in the environment
+// "${env.NAME}" is always the environment variable NAME
+#[derive(Debug,Default,Clone)]
+pub struct Variables {
+	values: BTreeMap<String,String>,
+}
+
+impl Variables {
+	pub fn new() -> Variables {
+		Variables::default()
+	}
+
+	pub fn set( &mut self, name: &str, value: &str ) {
+		self.values.insert( name.to_string(), value.to_string() );
+	}
+
+	pub fn get( &self, name: &str ) -> Option<String> {
+		match self.values.get( name ) {
+			Some( v ) => Some( v.clone() ),
+			None if name.is_empty() => None,
+			None => env::var( name.strip_prefix( "env." ).unwrap_or( name ) ).ok(),
+		}
+	}
+
+	// every variable has to be known
+	pub fn expand( &self, text: &str ) -> Result<String,OmError> {
+		let re = Regex::new(r"\$\{(.*?)\}").unwrap();
+		let mut unknown = None;
+		let expanded = re.replace_all( text, |c: &Captures| {
+			match self.get( &c[ 1 ] ) {
+				Some( v ) => v,
+				None => {
+					unknown.get_or_insert_with( || c[ 1 ].to_string() );
+					String::new()
+				},
+			}
+		});
+		match unknown {
+			None => Ok( expanded.to_string() ),
+			Some( name ) => Err( OmError::Generic( format!( "Unknown variable ${{{}}} in {:?}", name, text ) ) ),
+		}
+	}
+}
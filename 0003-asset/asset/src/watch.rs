@@ -0,0 +1,163 @@
+use log::{debug, error, info, warn};
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use omt_core::{OmError, Paths};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::{Asset, AssetBuild, ToolRun};
+
+// changes closer together than this are handled as one rebuild
+const DEBOUNCE_MS: u64 = 300;
+
+// keeps the data directory up to date while the content changes, until killed
+pub struct Watch {
+}
+
+impl Watch {
+	// the name a changed file has in the configs, below the content directory as given on the command line
+	fn content_name( asset_build: &AssetBuild, content_directory: &Path, path: &Path ) -> Option<String> {
+		Paths::relative( path, content_directory ).map( |name| {
+			Paths::normalize( &Paths::join( &asset_build.content_directory, &name ) )
+		})
+	}
+
+	// every run with an input naming, matching, or containing one of the changed files, and every run from a changed config
+	pub fn affected( tool_runs: &[ToolRun], changed: &[String] ) -> Vec<usize> {
+		tool_runs.iter().enumerate().filter( |( _, t )| {
+			let config_file = Paths::normalize( &t.config_file );
+			changed.iter().any( |c| *c == config_file || t.input.iter().any( |i| ToolRun::input_matches( i, c ) ) )
+		}).map( |( i, _ )| i ).collect()
+	}
+
+	// the tool runs to check after a change
+	// the root config and includes can change any tool run, so everything is checked then, and after a rescan
+	pub fn selected( tool_runs: &[ToolRun], changed: &[String], rescan: bool ) -> Vec<usize> {
+		let everything = rescan || changed.iter().any( |c| c.ends_with( ".yaml" ) && !c.ends_with( ".asset_config.yaml" ) );
+		if everything {
+			( 0..tool_runs.len() ).collect()
+		} else {
+			Watch::affected( tool_runs, changed )
+		}
+	}
+
+	// the build writes there, which must not trigger another build
+	// unless that would ignore the content too, e.g. with everything in "."
+	pub fn ignored_directories( asset_build: &AssetBuild, content_directory: &Path ) -> Vec<PathBuf> {
+		let mut ignored = Vec::new();
+		for d in &[ &asset_build.data_directory, &asset_build.temp_directory ] {
+			let _ = fs::create_dir_all( d );
+			match fs::canonicalize( d ) {
+				Ok( p ) if content_directory.starts_with( &p ) => {
+					warn!("📁 👀 {} contains the content directory {}, changes the build makes there are watched too", d, asset_build.content_directory );
+				},
+				Ok( p ) => ignored.push( p ),
+				Err( _ ) => {},
+			}
+		}
+		ignored
+	}
+
+	// the sorted content names of the changed paths, without the ones the build wrote
+	// content_directory and ignored are canonical, like the paths the watcher reports
+	pub fn changed_names( asset_build: &AssetBuild, content_directory: &Path, ignored: &[PathBuf], paths: &[PathBuf] ) -> Vec<String> {
+		let mut changed = paths.iter().filter( |p| !ignored.iter().any( |d| p.starts_with( d ) ) ).filter_map( |p| {
+			Watch::content_name( asset_build, content_directory, p )
+		}).collect::<Vec<_>>();
+		changed.sort();
+		changed.dedup();
+		changed
+	}
+
+	fn report( start: Instant, number_of_changes: usize, number_of_runs: usize, result: Result<u32,OmError> ) {
+		let took = start.elapsed().as_secs_f32();
+		match result {
+			Ok( n ) => info!("📁 👀 ✅ {} changes, {} tool runs, ~{} assets build in {:.2}s", number_of_changes, number_of_runs, n, took ),
+			Err( e ) => error!("📁 👀 ‼️ {} changes, {} tool runs, failed after {:.2}s: {}", number_of_changes, number_of_runs, took, e ),
+		}
+	}
+
+	pub fn run(
+		asset_build: &AssetBuild,
+	)
+	-> Result<u32,OmError> {
+		let start = Instant::now();
+		let mut tool_runs = Asset::collect_tool_runs( asset_build )?;
+		Watch::report( start, 0, tool_runs.len(), Asset::build_tool_runs( asset_build, &tool_runs, None ) );
+
+		let content_directory = match fs::canonicalize( &asset_build.content_directory ) {
+			Ok( d ) => d,
+			Err( e ) => return Err( OmError::Generic( format!( "Error watching {}: {}", asset_build.content_directory, e ) ) ),
+		};
+		let ignored = Watch::ignored_directories( asset_build, &content_directory );
+
+		let ( sender, receiver ) = mpsc::channel();
+		let mut watcher = match notify::watcher( sender, Duration::from_millis( DEBOUNCE_MS ) ) {
+			Ok( w ) => w,
+			Err( e ) => return Err( OmError::Generic( format!( "Error creating watcher: {}", e ) ) ),
+		};
+		if let Err( e ) = watcher.watch( &content_directory, RecursiveMode::Recursive ) {
+			return Err( OmError::Generic( format!( "Error watching {}: {}", asset_build.content_directory, e ) ) );
+		}
+		info!("📁 👀 Watching {} for changes", asset_build.content_directory );
+
+		loop {
+			// wait for the first change, then collect everything that follows closely
+			let mut events = match receiver.recv() {
+				Ok( e ) => vec![ e ],
+				Err( _ ) => return Err( OmError::Generic( "Watcher stopped".to_string() ) ),
+			};
+			while let Ok( e ) = receiver.recv_timeout( Duration::from_millis( DEBOUNCE_MS ) ) {
+				events.push( e );
+			}
+
+			let mut rescan = false;
+			let mut paths = Vec::new();
+			for event in events {
+				paths.extend( match event {
+					DebouncedEvent::Create( p )
+					| DebouncedEvent::Write( p )
+					| DebouncedEvent::Chmod( p )
+					| DebouncedEvent::Remove( p ) => vec![ p ],
+					DebouncedEvent::Rename( from, to ) => vec![ from, to ],
+					DebouncedEvent::Rescan => {
+						rescan = true;
+						Vec::new()
+					},
+					DebouncedEvent::Error( e, p ) => {
+						warn!("📁 👀 Watch error {} {:?}", e, p );
+						Vec::new()
+					},
+					// notices come early, the real event follows after the debounce delay
+					DebouncedEvent::NoticeWrite( _ ) | DebouncedEvent::NoticeRemove( _ ) => Vec::new(),
+				});
+			}
+			let changed = Watch::changed_names( asset_build, &content_directory, &ignored, &paths );
+			if changed.is_empty() && !rescan {
+				continue;
+			}
+			for c in &changed {
+				debug!("📁 👀 Changed {}", c );
+			}
+
+			let start = Instant::now();
+			// configs can be added, edited, or removed at any time, and new files can add foreach runs
+			tool_runs = match Asset::collect_tool_runs( asset_build ) {
+				Ok( t ) => t,
+				Err( e ) => {
+					error!("📁 👀 ‼️ {}", e );
+					continue;
+				},
+			};
+			let selected = Watch::selected( &tool_runs, &changed, rescan );
+			if selected.is_empty() {
+				debug!("📁 👀 {} changes, no tool run affected", changed.len() );
+				continue;
+			}
+			let result = Asset::build_tool_runs( asset_build, &tool_runs, Some( &selected ) );
+			Watch::report( start, changed.len(), selected.len(), result );
+		}
+	}
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use asset::{Asset, AssetConfig, ConfigCheck, ParameterValue, ToolRun, Variables};
+
+use std::env;
+
+fn collect( path: &str ) -> Vec<ToolRun> {
+	Asset::collect_tool_runs( &common::asset_build( path ) ).unwrap()
+}
+
+#[test]
+fn documents_win_over_root_defaults() {
+	let path = common::project( "documents_win_over_root_defaults" );
+	common::write( &path, "content/asset_config.yaml", "\
+tool: $asset
+command: copy
+parameters:
+  size: 1
+  border: 2
+variables:
+  name: root
+  kind: default
+" );
+	common::write( &path, "content/sub/test.asset_config.yaml", "\
+output: ${name}-${kind}.txt
+input: a.txt
+parameters:
+  size: 3
+variables:
+  name: doc
+---
+command: dump
+output: ${name}.txt
+" );
+	let tool_runs = collect( &path );
+	assert_eq!( tool_runs.len(), 2 );
+	let t = &tool_runs[ 0 ];
+	assert_eq!( ( t.tool.as_str(), t.command.as_str(), t.output.as_str() ), ( "$asset", "copy", "doc-default.txt" ) );
+	assert_eq!( t.parameters.get( "size" ), Some( &ParameterValue::IntegerValue( 3 ) ) );
+	assert_eq!( t.parameters.get( "border" ), Some( &ParameterValue::IntegerValue( 2 ) ) );
+	let t = &tool_runs[ 1 ];
+	assert_eq!( ( t.tool.as_str(), t.command.as_str(), t.output.as_str() ), ( "$asset", "dump", "root.txt" ) );
+	assert_eq!( t.parameters.len(), 2 );
+}
+
+#[test]
+fn includes_nest_and_later_ones_win() {
+	let path = common::project( "includes_nest_and_later_ones_win" );
+	common::write( &path, "content/shared/tool.yaml", "\
+include: base.yaml
+command: copy
+parameters:
+  size: 2
+" );
+	common::write( &path, "content/shared/base.yaml", "\
+tool: $asset
+command: dump
+parameters:
+  size: 1
+  border: 1
+" );
+	common::write( &path, "content/shared/big.yaml", "\
+parameters:
+  size: 3
+" );
+	common::write( &path, "content/sub/test.asset_config.yaml", "\
+include: [ ../shared/tool.yaml, ../shared/big.yaml ]
+output: out.txt
+input: a.txt
+" );
+	let tool_runs = collect( &path );
+	assert_eq!( tool_runs.len(), 1 );
+	let t = &tool_runs[ 0 ];
+	assert_eq!( ( t.tool.as_str(), t.command.as_str() ), ( "$asset", "copy" ) );
+	assert_eq!( t.parameters.get( "size" ), Some( &ParameterValue::IntegerValue( 3 ) ) );
+	assert_eq!( t.parameters.get( "border" ), Some( &ParameterValue::IntegerValue( 1 ) ) );
+}
+
+#[test]
+fn include_cycles_are_an_error() {
+	let path = common::project( "include_cycles_are_an_error" );
+	common::write( &path, "content/sub/a.yaml", "include: b.yaml\n" );
+	common::write( &path, "content/sub/b.yaml", "include: a.yaml\n" );
+	common::write( &path, "content/sub/test.asset_config.yaml", "\
+include: a.yaml
+tool: $asset
+command: dump
+" );
+	let problems = ConfigCheck::check( &common::asset_build( &path ) ).unwrap();
+	assert_eq!( problems.len(), 1 );
+	let e = problems[ 0 ].to_string();
+	assert!( e.contains( "Includes nested deeper than 16, is there a cycle?" ), "{}", e );
+}
+
+#[test]
+fn variables_expand_in_input_and_output() {
+	let path = common::project( "variables_expand_in_input_and_output" );
+	env::set_var( "ASSET_CONFIG_TEST_NAME", "from env" );
+	common::write( &path, "content/sub/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: ${ASSET_CONFIG_TEST_NAME}/${name}.txt
+input:
+  - my-${name}.txt
+  - my-${env.ASSET_CONFIG_TEST_NAME}.txt
+  - ${data_directory}/built.txt
+  - ${asset_path}/here.txt
+variables:
+  name: file
+" );
+	let tool_runs = collect( &path );
+	let t = &tool_runs[ 0 ];
+	assert_eq!( t.output, "from env/file.txt" );
+	assert_eq!( t.input, vec![
+		format!( "{}/content/sub/my-file.txt", path ),
+		format!( "{}/content/sub/my-from env.txt", path ),
+		format!( "{}/data/built.txt", path ),
+		format!( "{}/content/sub/here.txt", path ),
+	] );
+}
+
+#[test]
+fn inputs_are_relative_to_the_config() {
+	let path = common::project( "inputs_are_relative_to_the_config" );
+	common::write( &path, "content/top.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: top.txt
+input: a.txt
+" );
+	common::write( &path, "content/sub/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: sub.txt
+input: a.txt
+" );
+	let tool_runs = collect( &path );
+	let inputs = tool_runs.iter().map( |t| ( t.output.as_str(), t.input[ 0 ].clone() ) ).collect::<Vec<_>>();
+	assert!( inputs.contains( &( "top.txt", format!( "{}/content/a.txt", path ) ) ), "{:?}", inputs );
+	assert!( inputs.contains( &( "sub.txt", format!( "{}/content/sub/a.txt", path ) ) ), "{:?}", inputs );
+
+	// a config found as a plain file name, with content in the current directory
+	assert_eq!( AssetConfig::input_path( "", &Variables::new(), "a.txt" ).unwrap(), "a.txt" );
+}
+
+#[test]
+fn unknown_variables_are_an_error() {
+	let path = common::project( "unknown_variables_are_an_error" );
+	common::write( &path, "content/sub/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: ${not_a_variable_anywhere}.txt
+input: a.txt
+" );
+	let problems = ConfigCheck::check( &common::asset_build( &path ) ).unwrap();
+	assert_eq!( problems.len(), 1 );
+	let e = problems[ 0 ].to_string();
+	assert!( e.contains( "Unknown variable ${not_a_variable_anywhere}" ), "{}", e );
+}
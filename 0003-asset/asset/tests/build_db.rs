@@ -0,0 +1,134 @@
+mod common;
+
+use asset::{Asset, AssetBuild, BuildDb, BuildRecord, ParameterValue, ToolRun};
+
+use std::collections::HashMap;
+use std::fs;
+
+fn setup( name: &str ) -> ( String, AssetBuild ) {
+	let path = common::project( name );
+	common::write( &path, "content/in.txt", "first" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: out.txt
+input: in.txt
+" );
+	fs::create_dir_all( format!( "{}/data", path ) ).unwrap();
+	let asset_build = common::asset_build( &path );
+	( path, asset_build )
+}
+
+// the run the config describes, with changes
+fn tool_run( path: &str, command: &str, parameters: &HashMap<String,ParameterValue>, cmd_line: &str ) -> ToolRun {
+	let mut tool_run = ToolRun::new( "$asset", command, "out.txt", &[ format!( "{}/content/in.txt", path ) ], parameters, cmd_line );
+	tool_run.set_source( &format!( "{}/content/test.asset_config.yaml", path ), 0 );
+	tool_run
+}
+
+fn dirty_reason( asset_build: &AssetBuild, tool_run: &ToolRun ) -> Option<String> {
+	BuildDb::load( &asset_build.temp_directory ).dirty_reason( tool_run, &BuildRecord::create( asset_build, tool_run, &[] ) )
+}
+
+#[test]
+fn unchanged_run_is_up_to_date() {
+	let ( path, asset_build ) = setup( "unchanged_run_is_up_to_date" );
+	let copy = tool_run( &path, "copy", &HashMap::new(), "" );
+	assert_eq!( dirty_reason( &asset_build, &copy ), Some( "never built".to_string() ) );
+
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+	assert_eq!( dirty_reason( &asset_build, &copy ), None );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 0 );
+}
+
+#[test]
+fn changed_input_is_dirty() {
+	let ( path, asset_build ) = setup( "changed_input_is_dirty" );
+	Asset::build( &asset_build ).unwrap();
+	common::write( &path, "content/in.txt", "second" );
+
+	let copy = tool_run( &path, "copy", &HashMap::new(), "" );
+	assert_eq!( dirty_reason( &asset_build, &copy ), Some( format!( "input changed {}/content/in.txt", path ) ) );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+	assert_eq!( fs::read_to_string( format!( "{}/data/out.txt", path ) ).unwrap(), "second" );
+}
+
+#[test]
+fn changed_tool_or_parameters_are_dirty() {
+	let ( path, asset_build ) = setup( "changed_tool_or_parameters_are_dirty" );
+	Asset::build( &asset_build ).unwrap();
+
+	let dump = tool_run( &path, "dump", &HashMap::new(), "" );
+	assert_eq!( dirty_reason( &asset_build, &dump ), Some( "tool changed".to_string() ) );
+
+	let mut parameters = HashMap::new();
+	parameters.insert( "size".to_string(), ParameterValue::IntegerValue( 512 ) );
+	let sized = tool_run( &path, "copy", &parameters, "" );
+	assert_eq!( dirty_reason( &asset_build, &sized ), Some( "parameters changed".to_string() ) );
+
+	let with_cmd_line = tool_run( &path, "copy", &HashMap::new(), "${tool} ${input}" );
+	assert_eq!( dirty_reason( &asset_build, &with_cmd_line ), Some( "cmd_line changed".to_string() ) );
+
+	let mut renamed = tool_run( &path, "copy", &HashMap::new(), "" );
+	renamed.output = "renamed.txt".to_string();
+	assert_eq!( dirty_reason( &asset_build, &renamed ), Some( "output renamed".to_string() ) );
+
+	let mut with_shell = tool_run( &path, "copy", &HashMap::new(), "" );
+	with_shell.set_shell( true );
+	assert_eq!( dirty_reason( &asset_build, &with_shell ), Some( "cmd_line changed".to_string() ) );
+
+	let mut with_variables = tool_run( &path, "copy", &HashMap::new(), "" );
+	with_variables.variables.insert( "dir".to_string(), "other".to_string() );
+	assert_eq!( dirty_reason( &asset_build, &with_variables ), Some( "variables changed".to_string() ) );
+}
+
+#[test]
+fn missing_output_is_dirty() {
+	let ( path, asset_build ) = setup( "missing_output_is_dirty" );
+	Asset::build( &asset_build ).unwrap();
+
+	fs::remove_file( format!( "{}/data/out.txt", path ) ).unwrap();
+	let copy = tool_run( &path, "copy", &HashMap::new(), "" );
+	assert_eq!( dirty_reason( &asset_build, &copy ), Some( "output changed or missing".to_string() ) );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+}
+
+#[test]
+fn output_never_written_is_dirty() {
+	let ( path, asset_build ) = setup( "output_never_written_is_dirty" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: 'true'
+command: nothing
+output: never.txt
+cmd_line: ${tool}
+---
+tool: 'true'
+command: nothing
+output: pattern-%d
+cmd_line: ${tool}
+" );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 2 );
+	// only the pattern is fine without a file
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+}
+
+#[test]
+fn force_runs_up_to_date_runs() {
+	let ( _path, mut asset_build ) = setup( "force_runs_up_to_date_runs" );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 0 );
+
+	asset_build.set_force( true );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+}
+
+#[test]
+fn failed_run_fails_the_build_and_runs_again() {
+	let ( path, asset_build ) = setup( "failed_run_fails_the_build_and_runs_again" );
+	fs::remove_file( format!( "{}/content/in.txt", path ) ).unwrap();
+	assert!( Asset::build( &asset_build ).is_err() );
+	assert!( Asset::build( &asset_build ).is_err() );
+
+	common::write( &path, "content/in.txt", "back" );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+}
@@ -0,0 +1,170 @@
+use asset::{AssetBuild, BuildGraph, ToolRun};
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+fn run( output: &str, input: &[&str] ) -> ToolRun {
+	let input = input.iter().map( |i| i.to_string() ).collect::<Vec<_>>();
+	ToolRun::new( "$asset", "copy", output, &input, &HashMap::new(), "" )
+}
+
+// a -> b -> c, and d on its own
+fn chain() -> ( AssetBuild, Vec<ToolRun> ) {
+	let asset_build = AssetBuild::new( "content", "data", "temp", "", "" );
+	let tool_runs = vec![
+		run( "c.txt", &[ "data/b.txt" ] ),
+		run( "b.txt", &[ "data/a.txt" ] ),
+		run( "a.txt", &[ "content/a.txt" ] ),
+		run( "d.txt", &[ "content/d.txt" ] ),
+	];
+	( asset_build, tool_runs )
+}
+
+#[test]
+fn dependencies_come_from_outputs() {
+	let ( asset_build, mut tool_runs ) = chain();
+	tool_runs.push( run( "all.txt", &[ "data/*.txt" ] ) );
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+	assert_eq!( graph.dependencies, vec![ vec![ 1 ], vec![ 2 ], vec![], vec![], vec![ 0, 1, 2, 3 ] ] );
+	assert_eq!( graph.dependents, vec![ vec![ 4 ], vec![ 0, 4 ], vec![ 1, 4 ], vec![ 4 ], vec![] ] );
+}
+
+#[test]
+fn directories_and_patterns_match_outputs_below_them() {
+	let asset_build = AssetBuild::new( "content", "data", "temp", "", "" );
+	let tool_runs = vec![
+		run( "sub/a.png", &[ "content/a.png" ] ),
+		run( "sub/deeper/b.png", &[ "content/b.png" ] ),
+		run( "subway.png", &[ "content/c.png" ] ),
+		run( "dir.omar", &[ "./data/sub/" ] ),
+		run( "flat.omar", &[ "data/sub/*.png" ] ),
+		run( "all.omar", &[ "data/**/*.png" ] ),
+	];
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+	assert_eq!( graph.dependencies[ 3.. ], [ vec![ 0, 1 ], vec![ 0 ], vec![ 0, 1, 2 ] ] );
+}
+
+#[test]
+fn many_runs() {
+	// one run per file, like a big foreach, and one on all of them
+	let asset_build = AssetBuild::new( "content", "data", "temp", "", "" );
+	let mut tool_runs = ( 0..3000 ).map( |i| run( &format!( "sounds/{}.ogg", i ), &[ &format!( "content/sounds/{}.wav", i ) ] ) ).collect::<Vec<_>>();
+	tool_runs.push( run( "sounds.omar", &[ "data/sounds/*.ogg", "data/sounds" ] ) );
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+	assert_eq!( graph.dependencies[ 3000 ], ( 0..3000 ).collect::<Vec<_>>() );
+	assert!( graph.dependents[ .. 3000 ].iter().all( |d| d == &[ 3000 ] ) );
+}
+
+#[test]
+fn cycles_are_an_error() {
+	let asset_build = AssetBuild::new( "content", "data", "temp", "", "" );
+	let tool_runs = vec![
+		run( "a.txt", &[ "data/b.txt" ] ),
+		run( "b.txt", &[ "data/a.txt" ] ),
+	];
+	let e = BuildGraph::create( &asset_build, &tool_runs ).err().unwrap().to_string();
+	assert!( e.contains( "Tool runs depend on each other" ), "{}", e );
+}
+
+#[test]
+fn runs_after_dependencies() {
+	let ( asset_build, tool_runs ) = chain();
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+	assert_eq!( graph.order(), vec![ 2, 1, 0, 3 ] );
+
+	for jobs in 1..4 {
+		let started = Mutex::new( Vec::new() );
+		let mut finished = Vec::new();
+		let result = graph.run( jobs, false, |i| started.lock().unwrap().push( i ), |i, r| {
+			assert!( r.is_some() );
+			finished.push( i );
+			true
+		});
+		assert!( result.is_ok() );
+		let started = started.into_inner().unwrap();
+		let position = |i| started.iter().position( |s| *s == i ).unwrap();
+		assert!( position( 2 ) < position( 1 ), "{:?}", started );
+		assert!( position( 1 ) < position( 0 ), "{:?}", started );
+		finished.sort();
+		assert_eq!( finished, vec![ 0, 1, 2, 3 ] );
+	}
+}
+
+#[test]
+fn keep_going_skips_only_dependents() {
+	let ( asset_build, tool_runs ) = chain();
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+
+	let mut ran = Vec::new();
+	let mut skipped = Vec::new();
+	let result = graph.run( 1, true, |i| i, |i, r| match r {
+		Some( i ) => {
+			ran.push( i );
+			i != 1		// b fails
+		},
+		None => {
+			skipped.push( i );
+			false
+		},
+	});
+	assert!( result.is_ok() );
+	ran.sort();
+	assert_eq!( ran, vec![ 1, 2, 3 ] );
+	assert_eq!( skipped, vec![ 0 ] );
+}
+
+#[test]
+fn failure_without_keep_going_stops() {
+	let ( asset_build, tool_runs ) = chain();
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+
+	let mut ran = Vec::new();
+	let mut skipped = Vec::new();
+	let result = graph.run( 1, false, |i| i, |i, r| match r {
+		Some( i ) => {
+			ran.push( i );
+			i != 2		// a fails
+		},
+		None => {
+			skipped.push( i );
+			false
+		},
+	});
+	assert!( result.is_ok() );
+	// d may already have started when a failed
+	assert_eq!( ran[ 0 ], 2 );
+	assert!( !ran.contains( &1 ) && !ran.contains( &0 ), "{:?}", ran );
+	assert!( skipped.contains( &1 ) && skipped.contains( &0 ), "{:?}", skipped );
+	assert_eq!( ran.len() + skipped.len(), 4 );
+}
+
+#[test]
+fn panic_counts_as_failure() {
+	let ( asset_build, tool_runs ) = chain();
+	let graph = BuildGraph::create( &asset_build, &tool_runs ).unwrap();
+
+	for jobs in 1..3 {
+		let mut ran = Vec::new();
+		let mut skipped = Vec::new();
+		let result = graph.run( jobs, true, |i| {
+			if i == 1 {
+				panic!( "boom" );
+			}
+			i
+		}, |i, r| match r {
+			Some( i ) => {
+				ran.push( i );
+				true
+			},
+			None => {
+				skipped.push( i );
+				false
+			},
+		});
+		let e = result.unwrap_err().to_string();
+		assert!( e.contains( "tool run 1 panicked: boom" ), "{}", e );
+		ran.sort();
+		assert_eq!( ran, vec![ 2, 3 ] );
+		assert_eq!( skipped, vec![ 0 ] );
+	}
+}
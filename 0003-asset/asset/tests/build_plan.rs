@@ -0,0 +1,59 @@
+mod common;
+
+use asset::{Asset, BuildPlan, ToolRun};
+
+use std::collections::HashMap;
+
+#[test]
+fn plan_shows_what_would_run_and_why() {
+	let path = common::project( "plan_shows_what_would_run_and_why" );
+	common::write( &path, "content/a.txt", "a" );
+	common::write( &path, "content/b.txt", "b" );
+	let asset_build = common::asset_build( &path );
+	let mut copy_a = ToolRun::new( "$asset", "copy", "a.txt", &[ format!( "{}/content/a.txt", path ) ], &HashMap::new(), "" );
+	copy_a.set_source( "a.asset_config.yaml", 0 );
+	let mut copy_b = ToolRun::new( "$asset", "copy", "b.txt", &[ format!( "{}/content/b.txt", path ) ], &HashMap::new(), "" );
+	copy_b.set_source( "b.asset_config.yaml", 0 );
+	let mut echo = ToolRun::new( "echo", "test", "", &[ format!( "{}/data/a.txt", path ) ], &HashMap::new(), "${tool} ${input}" );
+	echo.set_source( "echo.asset_config.yaml", 0 );
+
+	Asset::build_tool_runs( &asset_build, &[ copy_a.clone(), copy_b.clone() ], None ).unwrap();
+	common::write( &path, "content/a.txt", "changed" );
+
+	let plan = BuildPlan::create_for( &asset_build, &[ echo, copy_b, copy_a ] ).unwrap();
+	assert_eq!( plan.order, vec![ 1, 2, 0 ] );
+	assert_eq!( plan.runs[ 0 ].dependencies, vec![ 2 ] );
+	assert_eq!( plan.runs[ 0 ].dirty.as_deref(), Some( "never built" ) );
+	assert_eq!( plan.runs[ 0 ].cmd_line, format!( "echo {}/data/a.txt", path ) );
+	assert_eq!( plan.runs[ 1 ].dirty, None );
+	assert_eq!( plan.runs[ 2 ].dirty, Some( format!( "input changed {}/content/a.txt", path ) ) );
+	assert_eq!( plan.runs[ 2 ].outputs, vec![ format!( "{}/data/a.txt", path ) ] );
+	assert_eq!( plan.dirty_count(), 2 );
+	assert_eq!( plan.to_text(), format!( "\
+Would run a.asset_config.yaml #0 $asset copy, input changed {path}/content/a.txt
+	{path}/content/a.txt -> {path}/data/a.txt
+Would run echo.asset_config.yaml #0 echo test, never built
+	echo {path}/data/a.txt
+", path = path ) );
+
+	// nothing ran
+	assert_eq!( std::fs::read_to_string( format!( "{}/data/a.txt", path ) ).unwrap(), "a" );
+}
+
+#[test]
+fn runs_depending_on_dirty_runs_are_dirty() {
+	let path = common::project( "runs_depending_on_dirty_runs_are_dirty" );
+	common::write( &path, "content/a.txt", "a" );
+	let asset_build = common::asset_build( &path );
+	let mut copy = ToolRun::new( "$asset", "copy", "a.txt", &[ format!( "{}/content/a.txt", path ) ], &HashMap::new(), "" );
+	copy.set_source( "copy.asset_config.yaml", 0 );
+	let mut copy_again = ToolRun::new( "$asset", "copy", "again.txt", &[ format!( "{}/data/a.txt", path ) ], &HashMap::new(), "" );
+	copy_again.set_source( "again.asset_config.yaml", 0 );
+
+	Asset::build_tool_runs( &asset_build, &[ copy.clone(), copy_again.clone() ], None ).unwrap();
+	common::write( &path, "content/a.txt", "changed" );
+
+	let plan = BuildPlan::create_for( &asset_build, &[ copy_again, copy ] ).unwrap();
+	assert_eq!( plan.runs[ 0 ].dirty.as_deref(), Some( "depends on copy.asset_config.yaml #0" ) );
+	assert!( plan.to_dot().contains( "\"run 0\" [shape=box,color=red" ) );
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use asset::{Asset, BuildReport, RunReport, RunStatus, ToolRun};
+
+use std::collections::HashMap;
+use std::fs;
+
+fn run( source: &str, status: RunStatus ) -> RunReport {
+	let mut tool_run = ToolRun::new( "convert", "resize", "out.png", &[], &HashMap::new(), "" );
+	tool_run.set_source( source, 0 );
+	RunReport::new( &tool_run, status )
+}
+
+#[test]
+fn counts_every_status() {
+	let mut report = BuildReport::new();
+	for status in &[ RunStatus::Built, RunStatus::UpToDate, RunStatus::UpToDate, RunStatus::Failed, RunStatus::NotRun, RunStatus::NotRun, RunStatus::NotRun ] {
+		report.add( run( "a.asset_config.yaml", *status ) );
+	}
+	assert_eq!( ( report.built, report.up_to_date, report.failed, report.not_run ), ( 1, 2, 1, 3 ) );
+
+	let junit = report.to_junit();
+	assert!( junit.contains( "<testsuites name=\"asset\" tests=\"7\" failures=\"1\" skipped=\"3\" time=\"0.000\">" ), "{}", junit );
+	assert!( junit.contains( "<testsuite name=\"asset build\" tests=\"7\" failures=\"1\" skipped=\"3\" time=\"0.000\">" ), "{}", junit );
+	assert_eq!( junit.matches( "<testcase " ).count(), 7 );
+	assert_eq!( junit.matches( "<failure " ).count(), 1 );
+	assert_eq!( junit.matches( "<skipped " ).count(), 3 );
+	assert_eq!( junit.matches( "<system-out>up to date</system-out>" ).count(), 2 );
+
+	let json: serde_json::Value = serde_json::from_str( &report.to_json().unwrap() ).unwrap();
+	assert_eq!( json[ "built" ], 1 );
+	assert_eq!( json[ "up_to_date" ], 2 );
+	assert_eq!( json[ "failed" ], 1 );
+	assert_eq!( json[ "not_run" ], 3 );
+	let statuses = json[ "runs" ].as_array().unwrap().iter().map( |r| r[ "status" ].as_str().unwrap().to_string() ).collect::<Vec<_>>();
+	assert_eq!( statuses, vec![ "built", "up_to_date", "up_to_date", "failed", "not_run", "not_run", "not_run" ] );
+}
+
+#[test]
+fn junit_escapes_text() {
+	let mut failed = run( "<a & \"b\">.asset_config.yaml", RunStatus::Failed );
+	failed.error = Some( "it's <broken> & \"gone\"".to_string() );
+	failed.cmd_line = "convert 'a b' > c".to_string();
+	failed.stdout = "line 1\n\x1b[31mred\x1b[0m\tand tab".to_string();
+	failed.stderr = "]]> \x07bell".to_string();
+	let mut report = BuildReport::new();
+	report.add( failed );
+
+	let junit = report.to_junit();
+	assert!( junit.contains( "<testcase classname=\"&lt;a &amp; &quot;b&quot;&gt;.asset_config.yaml\" name=\"&lt;a &amp; &quot;b&quot;&gt;.asset_config.yaml #0 convert resize\" time=\"0.000\">" ), "{}", junit );
+	assert!( junit.contains( "<failure message=\"it&apos;s &lt;broken&gt; &amp; &quot;gone&quot;\">convert &apos;a b&apos; &gt; c</failure>" ), "{}", junit );
+	// control characters xml can't hold are dropped, tabs and newlines are kept
+	assert!( junit.contains( "<system-out>line 1\n[31mred[0m\tand tab</system-out>" ), "{}", junit );
+	assert!( junit.contains( "<system-err>]]&gt; bell</system-err>" ), "{}", junit );
+
+	// json keeps everything as it is
+	let json: serde_json::Value = serde_json::from_str( &report.to_json().unwrap() ).unwrap();
+	assert_eq!( json[ "runs" ][ 0 ][ "error" ], "it's <broken> & \"gone\"" );
+	assert_eq!( json[ "runs" ][ 0 ][ "stdout" ], "line 1\n\x1b[31mred\x1b[0m\tand tab" );
+}
+
+#[test]
+fn build_writes_reports() {
+	let path = common::project( "build_writes_reports" );
+	common::write( &path, "content/in.txt", "data" );
+	let mut asset_build = common::asset_build( &path );
+	asset_build.add_report( &format!( "{}/report.json", path ) );
+	asset_build.add_report( &format!( "{}/report.xml", path ) );
+
+	let run = |output: &str, input: &str, doc_index: usize| {
+		let mut tool_run = ToolRun::new( "$asset", "copy", output, &[ format!( "{}/{}", path, input ) ], &HashMap::new(), "" );
+		tool_run.set_source( "test.asset_config.yaml", doc_index );
+		tool_run
+	};
+	let tool_runs = vec![
+		run( "good.txt", "content/in.txt", 0 ),
+		run( "bad.txt", "content/missing.txt", 1 ),
+		run( "after_bad.txt", "data/bad.txt", 2 ),
+	];
+	assert!( Asset::build_tool_runs( &asset_build, &tool_runs, None ).is_err() );
+
+	let json: serde_json::Value = serde_json::from_str( &fs::read_to_string( format!( "{}/report.json", path ) ).unwrap() ).unwrap();
+	assert_eq!( ( json[ "built" ].as_u64(), json[ "failed" ].as_u64(), json[ "not_run" ].as_u64() ), ( Some( 1 ), Some( 1 ), Some( 1 ) ), "{}", json );
+	let junit = fs::read_to_string( format!( "{}/report.xml", path ) ).unwrap();
+	assert!( junit.contains( "tests=\"3\" failures=\"1\" skipped=\"1\"" ), "{}", junit );
+}
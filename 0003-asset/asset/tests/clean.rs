@@ -0,0 +1,105 @@
+mod common;
+
+use asset::{Asset, AssetBuild, BuildDb, BuildRecord, RunLog};
+
+use std::fs;
+use std::path::Path;
+
+fn setup( name: &str ) -> ( String, AssetBuild ) {
+	let path = common::project( name );
+	common::write( &path, "content/a.txt", "a" );
+	common::write( &path, "content/b.txt", "b" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: a.txt
+input: a.txt
+---
+tool: $asset
+command: copy
+output: b.txt
+input: b.txt
+" );
+	fs::create_dir_all( format!( "{}/data", path ) ).unwrap();
+	let asset_build = common::asset_build( &path );
+	Asset::build( &asset_build ).unwrap();
+	( path, asset_build )
+}
+
+// a record for a config document that doesn't exist (anymore)
+fn add_record( asset_build: &AssetBuild, key: &str, outputs: &[&str] ) {
+	let mut build_db = BuildDb::load( &asset_build.temp_directory );
+	let mut record = BuildRecord::default();
+	for o in outputs {
+		record.outputs.insert( o.to_string(), "0x00000001".to_string() );
+	}
+	build_db.records.insert( key.to_string(), record );
+	build_db.save().unwrap();
+}
+
+fn exists( path: &str, name: &str ) -> bool {
+	Path::new( &format!( "{}/{}", path, name ) ).exists()
+}
+
+#[test]
+fn clean_removes_everything_the_build_wrote() {
+	let ( path, asset_build ) = setup( "clean_removes_everything_the_build_wrote" );
+	common::write( &path, "temp/intermediate.bin", "x" );
+	common::write( &path, "data/stale.txt", "not from the build" );
+	add_record( &asset_build, &format!( "{}/content/tool.asset_config.yaml#0", path ), &[ &format!( "{}/temp/intermediate.bin", path ) ] );
+	let config_file = format!( "{}/content/test.asset_config.yaml", path );
+	let logs = [ RunLog::filename_for( &asset_build.temp_directory, &format!( "{}#0", config_file ) ), RunLog::filename_for( &asset_build.temp_directory, &format!( "{}#1", config_file ) ) ];
+	assert!( logs.iter().all( |l| Path::new( l ).is_file() ) );
+
+	// a.txt, b.txt, intermediate.bin, two logs, and the build db
+	assert_eq!( Asset::clean( &asset_build, true ).unwrap(), 6 );
+	assert!( exists( &path, "data/a.txt" ) && exists( &path, "temp/intermediate.bin" ) && exists( &path, "temp/asset_build_db.json" ) );
+
+	assert_eq!( Asset::clean( &asset_build, false ).unwrap(), 6 );
+	assert!( !exists( &path, "data/a.txt" ) && !exists( &path, "data/b.txt" ) );
+	assert!( !exists( &path, "temp/intermediate.bin" ) && !exists( &path, "temp/asset_build_db.json" ) );
+	assert!( logs.iter().all( |l| !Path::new( l ).exists() ) );
+	assert!( exists( &path, "data/stale.txt" ) && exists( &path, "content/a.txt" ) );
+
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 2 );
+}
+
+#[test]
+fn prune_removes_outputs_of_deleted_documents() {
+	let ( path, asset_build ) = setup( "prune_removes_outputs_of_deleted_documents" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: a.txt
+input: a.txt
+" );
+	let log = RunLog::filename_for( &asset_build.temp_directory, &format!( "{}/content/test.asset_config.yaml#1", path ) );
+	assert!( Path::new( &log ).is_file() );
+
+	assert_eq!( Asset::prune( &asset_build, true ).unwrap(), 2 );
+	assert!( exists( &path, "data/b.txt" ) && Path::new( &log ).is_file() );
+	assert_eq!( BuildDb::load( &asset_build.temp_directory ).records.len(), 2 );
+
+	assert_eq!( Asset::prune( &asset_build, false ).unwrap(), 2 );
+	assert!( !exists( &path, "data/b.txt" ) && !Path::new( &log ).exists() );
+	assert!( exists( &path, "data/a.txt" ) );
+	assert_eq!( BuildDb::load( &asset_build.temp_directory ).records.len(), 1 );
+
+	assert_eq!( Asset::prune( &asset_build, false ).unwrap(), 0 );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 0 );
+}
+
+#[test]
+fn prune_keeps_outputs_of_live_documents() {
+	let ( path, asset_build ) = setup( "prune_keeps_outputs_of_live_documents" );
+	common::write( &path, "data/gone.txt", "only from the deleted document" );
+	add_record( &asset_build, &format!( "{}/content/deleted.asset_config.yaml#0", path ), &[
+		&format!( "{}/data/a.txt", path ),
+		&format!( "{}/data/gone.txt", path ),
+	] );
+
+	assert_eq!( Asset::prune( &asset_build, false ).unwrap(), 1 );
+	assert!( exists( &path, "data/a.txt" ) && exists( &path, "data/b.txt" ) );
+	assert!( !exists( &path, "data/gone.txt" ) );
+	assert_eq!( BuildDb::load( &asset_build.temp_directory ).records.len(), 2 );
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use asset::{Asset, CmdLine, ParameterValue, ToolRun};
+
+use std::collections::{BTreeMap, HashMap};
+
+fn tokens( cmd_line: &str ) -> Vec<String> {
+	CmdLine::tokenize( cmd_line ).unwrap()
+}
+
+fn tool_run( cmd_line: &str, input: &[&str] ) -> ToolRun {
+	let mut parameters = HashMap::new();
+	parameters.insert( "size".to_string(), ParameterValue::IntegerValue( 512 ) );
+	parameters.insert( "sizes".to_string(), ParameterValue::ListValue( vec![ ParameterValue::IntegerValue( 1 ), ParameterValue::IntegerValue( 2 ) ] ) );
+	parameters.insert( "none".to_string(), ParameterValue::NoValue );
+	let input = input.iter().map( |i| i.to_string() ).collect::<Vec<_>>();
+	ToolRun::new( "convert", "resize", "out dir/out.png", &input, &parameters, cmd_line )
+}
+
+#[test]
+fn tokenize_splits_on_whitespace_outside_quotes() {
+	assert_eq!( tokens( "  a  b\tc " ), vec![ "a", "b", "c" ] );
+	assert_eq!( tokens( "'a b' \"c d\" e\\ f" ), vec![ "a b", "c d", "e f" ] );
+	assert_eq!( tokens( "a'b c'd \"\"" ), vec![ "ab cd", "" ] );
+	assert_eq!( tokens( "'it\"s' \"it's\" \"a\\\"b\"" ), vec![ "it\"s", "it's", "a\"b" ] );
+	assert_eq!( tokens( "$HOME ; |" ), vec![ "$HOME", ";", "|" ] );
+}
+
+#[test]
+fn tokenize_rejects_unmatched_quotes() {
+	for cmd_line in &[ "a 'b", "a \"b", "a \\", "\"a\\" ] {
+		let e = CmdLine::tokenize( cmd_line ).unwrap_err().to_string();
+		assert!( e.contains( "in cmd_line" ), "{}: {}", cmd_line, e );
+	}
+}
+
+#[test]
+fn expand_keeps_values_in_one_argument() {
+	let tool_run = tool_run( "${tool} --output ${output} --size=${size} ${input}", &[ "a b.png", "c'd.png" ] );
+	assert_eq!( CmdLine::args( &tool_run, "data" ).unwrap(), vec![ "convert", "--output", "data/out dir/out.png", "--size=512", "a b.png", "c'd.png" ] );
+}
+
+#[test]
+fn expand_repeats_and_joins_lists() {
+	let tool_run = tool_run( "-i=${input} ${sizes} --sizes=${sizes:,} --inputs=${input:;}", &[ "a", "b" ] );
+	assert_eq!( CmdLine::args( &tool_run, "data" ).unwrap(), vec![ "-i=a", "-i=b", "1", "2", "--sizes=1,2", "--inputs=a;b" ] );
+}
+
+#[test]
+fn expand_drops_arguments_without_value() {
+	let tool_run = tool_run( "${tool} --flag=${none} ${} end", &[] );
+	assert_eq!( CmdLine::args( &tool_run, "data" ).unwrap(), vec![ "convert", "end" ] );
+}
+
+#[test]
+fn expand_rejects_unknown_placeholders() {
+	let tool_run = tool_run( "${tool} ${missing}", &[] );
+	let e = CmdLine::args( &tool_run, "data" ).unwrap_err().to_string();
+	assert!( e.contains( "Unknown placeholder ${missing}" ), "{}", e );
+}
+
+#[test]
+fn shell_line_quotes_values() {
+	let tool_run = tool_run( "${tool} ${input} > ${output} && echo ${sizes:,}", &[ "a b.png", "it's.png", "plain.png" ] );
+	assert_eq!(
+		CmdLine::shell_line( &tool_run, "data" ).unwrap(),
+		"convert 'a b.png' 'it'\\''s.png' plain.png > 'data/out dir/out.png' && echo 1,2"
+	);
+}
+
+#[test]
+fn variables_stay_in_one_argument() {
+	let mut tool_run = tool_run( "${tool} --dir=${dir} '${dir}/x' ${name:,}", &[] );
+	let mut variables = BTreeMap::new();
+	variables.insert( "dir".to_string(), "my dir/it's".to_string() );
+	variables.insert( "name".to_string(), "a \"b\" c".to_string() );
+	tool_run.set_variables( &variables );
+	assert_eq!( CmdLine::args( &tool_run, "data" ).unwrap(), vec![ "convert", "--dir=my dir/it's", "my dir/it's/x", "a \"b\" c" ] );
+
+	tool_run.set_shell( true );
+	tool_run.cmd_line = "${tool} ${dir} > ${name}; echo ${dir}".to_string();
+	assert_eq!(
+		CmdLine::shell_line( &tool_run, "data" ).unwrap(),
+		"convert 'my dir/it'\\''s' > 'a \"b\" c'; echo 'my dir/it'\\''s'"
+	);
+}
+
+#[test]
+fn config_variables_are_expanded_per_argument() {
+	let path = common::project( "config_variables_are_expanded_per_argument" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: echo
+command: test
+output: out.txt
+variables:
+  dir: my dir/it's
+  size: 16
+parameters:
+  size: 512
+cmd_line: ${tool} --dir=${dir} --size=${size} ${HOME_IS_NOT_SET_HERE_4711}
+" );
+	let asset_build = common::asset_build( &path );
+	let tool_runs = Asset::collect_tool_runs( &asset_build ).unwrap();
+	assert_eq!( tool_runs.len(), 1 );
+	// parameters win over variables, and unknown names are left for the tool run to report
+	assert_eq!( tool_runs[ 0 ].variables.keys().collect::<Vec<_>>(), vec![ "dir" ] );
+	let e = CmdLine::args( &tool_runs[ 0 ], "data" ).unwrap_err().to_string();
+	assert!( e.contains( "Unknown placeholder ${HOME_IS_NOT_SET_HERE_4711}" ), "{}", e );
+
+	let mut tool_run = tool_runs[ 0 ].clone();
+	tool_run.cmd_line = tool_run.cmd_line.replace( " ${HOME_IS_NOT_SET_HERE_4711}", "" );
+	assert_eq!( CmdLine::args( &tool_run, "data" ).unwrap(), vec![ "echo", "--dir=my dir/it's", "--size=512" ] );
+}
@@ -0,0 +1,36 @@
+// setup shared by the integration tests
+// every test works in its own project directory below the cargo tmp dir, with content, data and temp in it
+#![allow(dead_code)]	// not every test uses everything
+
+use asset::AssetBuild;
+
+use std::fs;
+use std::path::Path;
+
+// an empty project with only the content directory, recreated for every run
+pub fn project( name: &str ) -> String {
+	let path = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( name );
+	let _ = fs::remove_dir_all( &path );
+	fs::create_dir_all( path.join( "content" ) ).unwrap();
+	path.to_string_lossy().to_string()
+}
+
+// writes a file below the project, with all directories leading to it
+pub fn write( path: &str, name: &str, data: &str ) {
+	let filename = Path::new( path ).join( name );
+	if let Some( parent ) = filename.parent() {
+		fs::create_dir_all( parent ).unwrap();
+	}
+	fs::write( filename, data ).unwrap();
+}
+
+// builds content into data, using temp, without archive and paklist
+pub fn asset_build( path: &str ) -> AssetBuild {
+	AssetBuild::new(
+		&format!( "{}/content", path ),
+		&format!( "{}/data", path ),
+		&format!( "{}/temp", path ),
+		"",
+		"",
+	)
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use asset::{ConfigCheck, ConfigProblem, Severity};
+
+fn check( name: &str, config: &str ) -> Vec<ConfigProblem> {
+	let path = common::project( name );
+	common::write( &path, "content/test.asset_config.yaml", config );
+	ConfigCheck::check( &common::asset_build( &path ) ).unwrap()
+}
+
+fn positions( problems: &[ConfigProblem] ) -> Vec<( Severity, usize, usize, usize )> {
+	problems.iter().map( |p| ( p.severity, p.doc_index, p.line, p.col ) ).collect()
+}
+
+#[test]
+fn valid_config_has_no_problems() {
+	let problems = check( "valid_config_has_no_problems", "\
+tool: $asset
+command: copy
+output: out.txt
+input: in.txt
+" );
+	assert!( problems.is_empty(), "{:?}", problems );
+}
+
+#[test]
+fn problems_point_at_the_key() {
+	let problems = check( "problems_point_at_the_key", "\
+tool: $asset
+command: copy
+output: out.txt
+inptu: in.txt
+---
+tool: $asset
+command: squash
+parameters:
+  size: 512
+  sizes:
+    - 1
+    - { nested: [ 1 ] }
+  shell: 1
+input:
+  - a.txt
+  - [ b.txt ]
+" );
+	assert_eq!( positions( &problems ), vec![
+		( Severity::Error, 0, 4, 1 ),
+		( Severity::Error, 1, 7, 1 ),
+		( Severity::Error, 1, 16, 5 ),
+	], "{:?}", problems );
+	assert!( problems[ 0 ].message.contains( "Unknown key \"inptu\"" ) );
+	assert!( problems[ 1 ].message.contains( "Unknown $asset command \"squash\"" ) );
+	assert!( problems[ 2 ].message.contains( "input must be a string" ) );
+	assert!( problems[ 2 ].to_string().ends_with( "test.asset_config.yaml:16:5: document #1: input must be a string, not Array([String(\"b.txt\")])" ), "{}", problems[ 2 ] );
+}
+
+#[test]
+fn whole_document_problems_point_at_the_document() {
+	let problems = check( "whole_document_problems_point_at_the_document", "\
+tool: $asset
+command: dump
+---
+# no tool
+output: out.txt
+---
+tool: $asset
+command: copy
+cmd_line: ignored ${input}
+" );
+	// the document starts at its "---"
+	assert_eq!( positions( &problems ), vec![
+		( Severity::Error, 1, 3, 1 ),
+		( Severity::Warning, 2, 9, 1 ),
+	], "{:?}", problems );
+	assert!( problems[ 0 ].message.contains( "Missing tool" ) );
+	assert!( problems[ 1 ].message.contains( "cmd_line is ignored" ) );
+}
+
+#[test]
+fn invalid_yaml_points_at_the_error() {
+	let problems = check( "invalid_yaml_points_at_the_error", "\
+tool: $asset
+command: [ copy
+" );
+	assert_eq!( problems.len(), 1 );
+	assert_eq!( problems[ 0 ].severity, Severity::Error );
+	assert_eq!( problems[ 0 ].line, 3 );
+	assert!( problems[ 0 ].message.starts_with( "Invalid yaml" ), "{}", problems[ 0 ] );
+}
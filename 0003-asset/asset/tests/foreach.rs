@@ -0,0 +1,93 @@
+mod common;
+
+use asset::{Asset, BuildDb, CmdLine};
+
+#[test]
+fn foreach_makes_one_run_per_file() {
+	let path = common::project( "foreach_makes_one_run_per_file" );
+	common::write( &path, "content/sounds/b.wav", "" );
+	common::write( &path, "content/sounds/sub/a.wav", "" );
+	common::write( &path, "content/sounds/readme.txt", "" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: sox
+command: convert
+foreach: sounds/**/*.wav
+input: common.txt
+output: ${input.stem}.${input.ext}.ogg
+cmd_line: ${tool} ${input} -o ${output}
+" );
+	let asset_build = common::asset_build( &path );
+	let tool_runs = Asset::collect_tool_runs( &asset_build ).unwrap();
+	assert_eq!( tool_runs.len(), 2 );
+
+	let foreach_inputs = tool_runs.iter().map( |t| t.foreach_input.as_str() ).collect::<Vec<_>>();
+	assert_eq!( foreach_inputs, vec![ "b.wav", "sub/a.wav" ] );
+	let outputs = tool_runs.iter().map( |t| t.output.as_str() ).collect::<Vec<_>>();
+	assert_eq!( outputs, vec![ "b.wav.ogg", "a.wav.ogg" ] );
+
+	// the file comes first, then the inputs of the document
+	assert_eq!( tool_runs[ 1 ].input, vec![ format!( "{}/content/sounds/sub/a.wav", path ), format!( "{}/content/common.txt", path ) ] );
+
+	// every file gets its own build db record
+	let config_file = format!( "{}/content/test.asset_config.yaml", path );
+	assert_eq!( BuildDb::key( &tool_runs[ 0 ] ), format!( "{}#0#b.wav", config_file ) );
+	assert_eq!( BuildDb::key( &tool_runs[ 1 ] ), format!( "{}#0#sub/a.wav", config_file ) );
+}
+
+#[test]
+fn foreach_without_matches_makes_no_runs() {
+	let path = common::project( "foreach_without_matches_makes_no_runs" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: sox
+command: convert
+foreach: sounds/*.wav
+output: ${input.stem}.ogg
+" );
+	let tool_runs = Asset::collect_tool_runs( &common::asset_build( &path ) ).unwrap();
+	assert!( tool_runs.is_empty() );
+}
+
+#[test]
+fn foreach_variables_stay_in_one_argument() {
+	let path = common::project( "foreach_variables_stay_in_one_argument" );
+	common::write( &path, "content/sounds/it's.wav", "" );
+	common::write( &path, "content/sounds/sub dir/a b.wav", "" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: sox
+command: convert
+foreach: sounds/**/*.wav
+output: ${input.dir}/${input.stem}.ogg
+cmd_line: ${tool} ${input.relpath} --dir=${input.dir} -o ${input.stem}.${input.ext}.ogg
+" );
+	let tool_runs = Asset::collect_tool_runs( &common::asset_build( &path ) ).unwrap();
+	let outputs = tool_runs.iter().map( |t| t.output.as_str() ).collect::<Vec<_>>();
+	assert_eq!( outputs, vec![ "it's.ogg", "sub dir/a b.ogg" ] );
+
+	assert_eq!( CmdLine::args( &tool_runs[ 0 ], "data" ).unwrap(), vec![ "sox", "it's.wav", "--dir=.", "-o", "it's.wav.ogg" ] );
+	assert_eq!( CmdLine::args( &tool_runs[ 1 ], "data" ).unwrap(), vec![ "sox", "sub dir/a b.wav", "--dir=sub dir", "-o", "a b.wav.ogg" ] );
+
+	let mut tool_run = tool_runs[ 0 ].clone();
+	tool_run.set_shell( true );
+	assert_eq!( CmdLine::shell_line( &tool_run, "data" ).unwrap(), "sox 'it'\\''s.wav' --dir=. -o 'it'\\''s'.wav.ogg" );
+}
+
+#[test]
+fn foreach_outputs_at_the_top_are_plain_names() {
+	let path = common::project( "foreach_outputs_at_the_top_are_plain_names" );
+	common::write( &path, "content/texts/a.txt", "a" );
+	common::write( &path, "content/texts/sub/b.txt", "b" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+foreach: texts
+output: ${input.dir}/${input.stem}.copy
+" );
+	let mut asset_build = common::asset_build( &path );
+	asset_build.paklist = format!( "{}/final.paklist", path );
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 2 );
+	assert_eq!( std::fs::read_to_string( &asset_build.paklist ).unwrap(), "a.copy\nsub/b.copy\n" );
+
+	let build_db = BuildDb::load( &asset_build.temp_directory );
+	let outputs = build_db.records.values().flat_map( |r| r.outputs.keys().cloned() ).collect::<Vec<_>>();
+	assert_eq!( outputs, vec![ format!( "{}/data/a.copy", path ), format!( "{}/data/sub/b.copy", path ) ] );
+}
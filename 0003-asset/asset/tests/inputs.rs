@@ -0,0 +1,95 @@
+mod common;
+
+use asset::{Asset, AssetBuild, BuildDb, BuildRecord, ToolRun};
+
+use std::collections::HashMap;
+use std::fs;
+
+fn setup( name: &str ) -> String {
+	let path = common::project( name );
+	for f in &[ "images/b.png", "images/a.png", "images/notes.txt", "images/sub/c.png", "images/sub/deeper/d.png" ] {
+		common::write( &path, &format!( "content/{}", f ), f );
+	}
+	path
+}
+
+fn input_files( path: &str, input: &[&str] ) -> Vec<String> {
+	let input = input.iter().map( |i| format!( "{}/content/{}", path, i ) ).collect::<Vec<_>>();
+	let tool_run = ToolRun::new( "$asset", "dump", "", &input, &HashMap::new(), "" );
+	tool_run.input_files().iter().map( |f| f.trim_start_matches( &format!( "{}/content/", path ) ).to_string() ).collect()
+}
+
+#[test]
+fn patterns_expand_to_sorted_files() {
+	let path = setup( "patterns_expand_to_sorted_files" );
+	assert_eq!( input_files( &path, &[ "images/*.png" ] ), vec![ "images/a.png", "images/b.png" ] );
+	assert_eq!( input_files( &path, &[ "images/**/*.png" ] ), vec![ "images/a.png", "images/b.png", "images/sub/c.png", "images/sub/deeper/d.png" ] );
+	// directories don't match, even when the pattern does
+	assert_eq!( input_files( &path, &[ "images/s*" ] ), Vec::<String>::new() );
+}
+
+#[test]
+fn directories_expand_to_all_files_below() {
+	let path = setup( "directories_expand_to_all_files_below" );
+	assert_eq!( input_files( &path, &[ "images/sub" ] ), vec![ "images/sub/c.png", "images/sub/deeper/d.png" ] );
+	assert_eq!( input_files( &path, &[ "images/sub/" ] ), vec![ "images/sub/c.png", "images/sub/deeper/d.png" ] );
+}
+
+#[test]
+fn inputs_keep_their_order_without_duplicates() {
+	let path = setup( "inputs_keep_their_order_without_duplicates" );
+	assert_eq!(
+		input_files( &path, &[ "images/sub", "images/*.png", "images/a.png", "missing.png" ] ),
+		vec![ "images/sub/c.png", "images/sub/deeper/d.png", "images/a.png", "images/b.png", "missing.png" ]
+	);
+}
+
+#[test]
+fn inputs_match_files_by_name_pattern_and_directory() {
+	assert!( ToolRun::input_matches( "content/a.png", "content/./a.png" ) );
+	assert!( ToolRun::input_matches( "content/images", "content/images/sub/c.png" ) );
+	assert!( !ToolRun::input_matches( "content/images", "content/images2/c.png" ) );
+	assert!( ToolRun::input_matches( "content/*.png", "content/a.png" ) );
+	assert!( !ToolRun::input_matches( "content/*.png", "content/sub/a.png" ) );
+	assert!( ToolRun::input_matches( "content/**/*.png", "content/sub/deeper/a.png" ) );
+	assert!( !ToolRun::input_matches( "content/*.png", "content/a.txt" ) );
+}
+
+fn dirty_reasons( asset_build: &AssetBuild ) -> Vec<Option<String>> {
+	let build_db = BuildDb::load( &asset_build.temp_directory );
+	Asset::collect_tool_runs( asset_build ).unwrap().iter().map( |t| {
+		build_db.dirty_reason( t, &BuildRecord::create( asset_build, t, &[] ) )
+	}).collect()
+}
+
+#[test]
+fn adding_a_matching_file_runs_again() {
+	let path = setup( "adding_a_matching_file_runs_again" );
+	common::write( &path, "content/images.asset_config.yaml", "\
+tool: $asset
+command: dump
+input: images/*.png
+---
+tool: $asset
+command: dump
+input: images/sub
+" );
+	let asset_build = common::asset_build( &path );
+	Asset::build( &asset_build ).unwrap();
+	assert_eq!( dirty_reasons( &asset_build ), vec![ None, None ] );
+
+	common::write( &path, "content/images/e.png", "e" );
+	common::write( &path, "content/images/e.txt", "e" );
+	assert_eq!( dirty_reasons( &asset_build ), vec![ Some( format!( "input added {}/content/images/e.png", path ) ), None ] );
+
+	Asset::build( &asset_build ).unwrap();
+	common::write( &path, "content/images/sub/new/f.png", "f" );
+	fs::remove_file( format!( "{}/content/images/a.png", path ) ).unwrap();
+	assert_eq!( dirty_reasons( &asset_build ), vec![
+		Some( format!( "input removed {}/content/images/a.png", path ) ),
+		Some( format!( "input added {}/content/images/sub/new/f.png", path ) ),
+	] );
+
+	Asset::build( &asset_build ).unwrap();
+	assert_eq!( dirty_reasons( &asset_build ), vec![ None, None ] );
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use asset::{Asset, AssetBuild, BuildRecord, ParameterValue, ToolRun};
+use packer::Archive;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+fn setup( name: &str ) -> ( String, AssetBuild ) {
+	let path = common::project( name );
+	common::write( &path, "content/a.txt", "a" );
+	common::write( &path, "content/b.txt", "b" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: $asset
+command: copy
+output: sub/b.txt
+input: b.txt
+---
+tool: $asset
+command: copy
+output: a.txt
+input: a.txt
+" );
+	fs::create_dir_all( format!( "{}/data/sub", path ) ).unwrap();
+	let mut asset_build = common::asset_build( &path );
+	asset_build.paklist = format!( "{}/final.paklist", path );
+	asset_build.archive = format!( "{}/final.omar", path );
+	( path, asset_build )
+}
+
+#[test]
+fn paklist_lists_what_the_build_wrote() {
+	let ( path, asset_build ) = setup( "paklist_lists_what_the_build_wrote" );
+	common::write( &path, "data/stale.txt", "not from the build" );
+
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 2 );
+	assert_eq!( fs::read_to_string( &asset_build.paklist ).unwrap(), "a.txt\nsub/b.txt\n" );
+	assert!( fs::metadata( &asset_build.archive ).unwrap().len() > 0 );
+
+	// up to date runs still count
+	fs::remove_file( &asset_build.paklist ).unwrap();
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 0 );
+	assert_eq!( fs::read_to_string( &asset_build.paklist ).unwrap(), "a.txt\nsub/b.txt\n" );
+}
+
+#[test]
+fn paklist_in_the_data_directory_is_not_listed() {
+	let ( path, mut asset_build ) = setup( "paklist_in_the_data_directory_is_not_listed" );
+	asset_build.paklist = format!( "{}/data/final.paklist", path );
+	asset_build.archive = format!( "{}/data/final.omar", path );
+
+	Asset::build( &asset_build ).unwrap();
+	Asset::build( &asset_build ).unwrap();
+	assert_eq!( fs::read_to_string( &asset_build.paklist ).unwrap(), "a.txt\nsub/b.txt\n" );
+}
+
+#[test]
+fn packed_archives_are_not_in_the_paklist() {
+	let path = common::project( "packed_archives_are_not_in_the_paklist" );
+	common::write( &path, "content/in.txt", "data" );
+	let mut asset_build = common::asset_build( &path );
+	asset_build.paklist = format!( "{}/final.paklist", path );
+	asset_build.archive = format!( "{}/final.omar", path );
+
+	let copy = ToolRun::new( "$asset", "copy", "sub/out.txt", &[ format!( "{}/content/in.txt", path ) ], &HashMap::new(), "" );
+	let mut pack = ToolRun::new( "$asset", "pack", "sub.omar", &[ format!( "{}/data/sub", path ) ], &HashMap::new(), "" );
+	pack.set_source( "pack.asset_config.yaml", 0 );
+	Asset::build_tool_runs( &asset_build, &[ copy, pack ], None ).unwrap();
+
+	assert!( Path::new( &format!( "{}/data/sub.omar", path ) ).is_file() );
+	assert_eq!( fs::read_to_string( &asset_build.paklist ).unwrap(), "sub/out.txt\n" );
+}
+
+#[test]
+fn paklist_for_current_directory() {
+	let mut record = BuildRecord::default();
+	for f in &[ "./out.txt", "sub/b.txt", "../outside.txt", "/abs.txt" ] {
+		record.outputs.insert( f.to_string(), "0x00000001".to_string() );
+	}
+	record.outputs.insert( "missing.txt".to_string(), String::new() );
+	assert_eq!( record.data_files( "." ), vec![ "out.txt", "sub/b.txt" ] );
+	assert_eq!( record.data_files( "sub" ), vec![ "b.txt" ] );
+}
+
+#[test]
+fn paklist_lists_outputs_of_external_tools() {
+	let path = common::project( "paklist_lists_outputs_of_external_tools" );
+	common::write( &path, "content/a.txt", "a" );
+	common::write( &path, "content/test.asset_config.yaml", "\
+tool: cp
+command: copy
+output: sub/b.txt
+input: a.txt
+cmd_line: ${tool} ${input} ${output}
+" );
+	let mut asset_build = common::asset_build( &path );
+	asset_build.paklist = format!( "{}/final.paklist", path );
+
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 1 );
+	assert_eq!( fs::read_to_string( format!( "{}/data/sub/b.txt", path ) ).unwrap(), "a" );
+	assert_eq!( fs::read_to_string( &asset_build.paklist ).unwrap(), "sub/b.txt\n" );
+	assert!( !Path::new( &format!( "{}/sub/b.txt", path ) ).exists() );
+
+	// the output was recorded, so nothing runs again
+	assert_eq!( Asset::build( &asset_build ).unwrap(), 0 );
+}
+
+#[test]
+fn pack_reads_names_from_paklists() {
+	let path = common::project( "pack_reads_names_from_paklists" );
+	common::write( &path, "data/a.txt", "a" );
+	common::write( &path, "data/b.txt", "b" );
+	common::write( &path, "data/c.txt", "c" );
+	common::write( &path, "first.paklist", "a.txt\nb.txt\n" );
+	common::write( &path, "second.paklist", "c.txt\n" );
+	let asset_build = common::asset_build( &path );
+
+	let pack = |output: &str, paklist: ParameterValue| {
+		let mut parameters = HashMap::new();
+		parameters.insert( "paklist".to_string(), paklist );
+		let mut pack = ToolRun::new( "$asset", "pack", output, &[], &parameters, "" );
+		pack.set_source( "pack.asset_config.yaml", 0 );
+		Asset::build_tool_runs( &asset_build, &[ pack ], None )
+	};
+	let paklist = |name: &str| ParameterValue::StringValue( format!( "{}/{}", path, name ) );
+	let entries = |name: &str| {
+		let mut archive = Archive::create( "" );
+		archive.load( &format!( "{}/data/{}", path, name ) ).unwrap();
+		archive.entries.len()
+	};
+
+	assert!( pack( "one.omar", paklist( "first.paklist" ) ).is_ok() );
+	assert_eq!( entries( "one.omar" ), 2 );
+	assert!( pack( "both.omar", ParameterValue::ListValue( vec![ paklist( "first.paklist" ), paklist( "second.paklist" ) ] ) ).is_ok() );
+	assert_eq!( entries( "both.omar" ), 3 );
+	assert!( pack( "broken.omar", ParameterValue::ListValue( vec![ ParameterValue::IntegerValue( 1 ) ] ) ).is_err() );
+}
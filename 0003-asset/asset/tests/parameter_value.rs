@@ -0,0 +1,107 @@
+use asset::{ParameterValue, ToolRun};
+use yaml_rust::YamlLoader;
+
+use std::collections::HashMap;
+
+fn from_yaml( yaml: &str ) -> ParameterValue {
+	let docs = YamlLoader::load_from_str( yaml ).unwrap();
+	ParameterValue::from_yaml( &docs[ 0 ] ).unwrap()
+}
+
+#[test]
+fn scalars_format_plain() {
+	assert_eq!( from_yaml( "512" ).to_arg(), "512" );
+	assert_eq!( from_yaml( "-3" ).to_arg(), "-3" );
+	assert_eq!( from_yaml( "0.5" ).to_arg(), "0.5" );
+	assert_eq!( from_yaml( "2.0" ).to_arg(), "2" );
+	assert_eq!( from_yaml( "true" ).to_arg(), "true" );
+	assert_eq!( from_yaml( "false" ).to_arg(), "false" );
+	assert_eq!( from_yaml( "\"a b\"" ).to_arg(), "a b" );
+	assert_eq!( from_yaml( "~" ).to_arg(), "" );
+
+	assert_eq!( from_yaml( "0.5" ).to_args(), vec![ "0.5" ] );
+	assert_eq!( from_yaml( "false" ).to_args(), vec![ "false" ] );
+	assert!( from_yaml( "~" ).to_args().is_empty() );
+}
+
+#[test]
+fn lists_join_with_comma() {
+	let list = from_yaml( "[ 1, 2.5, true, x ]" );
+	assert_eq!( list.to_arg(), "1,2.5,true,x" );
+	assert_eq!( list.to_args(), vec![ "1", "2.5", "true", "x" ] );
+	assert!( from_yaml( "[]" ).to_args().is_empty() );
+	assert_eq!( from_yaml( "[]" ).to_arg(), "" );
+
+	// nested lists are flattened into one argument
+	let nested = from_yaml( "[ a, [ b, c ] ]" );
+	assert_eq!( nested.to_args(), vec![ "a", "b,c" ] );
+}
+
+#[test]
+fn maps_are_sorted_key_value_pairs() {
+	let map = from_yaml( "{ size: 512, filter: linear, scale: 0.5, flip: false }" );
+	assert_eq!( map.to_args(), vec![ "filter=linear", "flip=false", "scale=0.5", "size=512" ] );
+	assert_eq!( map.to_arg(), "filter=linear,flip=false,scale=0.5,size=512" );
+	assert_eq!( from_yaml( "{ sizes: [ 1, 2 ], none: ~ }" ).to_args(), vec![ "none=", "sizes=1,2" ] );
+}
+
+#[test]
+fn unsupported_yaml_is_an_error() {
+	let docs = YamlLoader::load_from_str( "{ [ a ]: b }" ).unwrap();
+	let e = ParameterValue::from_yaml( &docs[ 0 ] ).unwrap_err();
+	assert!( e.contains( "is not a string" ), "{}", e );
+}
+
+// a tool run with the parameters from a yaml mapping
+fn tool_run( yaml: &str ) -> ToolRun {
+	let parameters = match from_yaml( yaml ) {
+		ParameterValue::MapValue( m ) => m.into_iter().collect::<HashMap<_,_>>(),
+		v => panic!( "not a map {}", v ),
+	};
+	ToolRun::new( "$asset", "atlas", "", &[], &parameters, "" )
+}
+
+#[test]
+fn typed_parameters_accept_matching_values() {
+	let t = tool_run( "{ size: 512, text_size: \"256\", scale: 0.5, whole: 2, text_scale: \"1.5\", flip: true, text_flip: \"false\", names: [ a, b ], name: a, options: { a: 1 }, none: ~ }" );
+	assert_eq!( t.integer_parameter( "size", 0 ).unwrap(), 512 );
+	assert_eq!( t.integer_parameter( "text_size", 0 ).unwrap(), 256 );
+	assert_eq!( t.float_parameter( "scale", 1.0 ).unwrap(), 0.5 );
+	assert_eq!( t.float_parameter( "whole", 1.0 ).unwrap(), 2.0 );
+	assert_eq!( t.float_parameter( "text_scale", 1.0 ).unwrap(), 1.5 );
+	assert!( t.bool_parameter( "flip", false ).unwrap() );
+	assert!( !t.bool_parameter( "text_flip", true ).unwrap() );
+	assert_eq!( t.string_parameter( "name" ).unwrap(), Some( "a".to_string() ) );
+	assert_eq!( t.list_parameter( "names" ).unwrap(), vec![ ParameterValue::StringValue( "a".to_string() ), ParameterValue::StringValue( "b".to_string() ) ] );
+	// a single value is a list with one item
+	assert_eq!( t.list_parameter( "name" ).unwrap(), vec![ ParameterValue::StringValue( "a".to_string() ) ] );
+	assert_eq!( t.map_parameter( "options" ).unwrap().get( "a" ), Some( &ParameterValue::IntegerValue( 1 ) ) );
+}
+
+#[test]
+fn typed_parameters_default_when_missing() {
+	let t = tool_run( "{ none: ~ }" );
+	for name in &[ "none", "missing" ] {
+		assert_eq!( t.integer_parameter( name, 7 ).unwrap(), 7 );
+		assert_eq!( t.float_parameter( name, 0.25 ).unwrap(), 0.25 );
+		assert!( t.bool_parameter( name, true ).unwrap() );
+		assert_eq!( t.string_parameter( name ).unwrap(), None );
+		assert!( t.list_parameter( name ).unwrap().is_empty() );
+		assert!( t.map_parameter( name ).unwrap().is_empty() );
+	}
+}
+
+#[test]
+fn typed_parameters_reject_other_values() {
+	let t = tool_run( "{ size: big, scale: [ 1 ], flip: 1, name: 2, options: [ a ] }" );
+	let e = t.integer_parameter( "size", 0 ).unwrap_err().to_string();
+	assert!( e.contains( "Parameter size is not an integer: \"big\"" ), "{}", e );
+	let e = t.float_parameter( "scale", 0.0 ).unwrap_err().to_string();
+	assert!( e.contains( "Parameter scale is not a number: [1]" ), "{}", e );
+	let e = t.bool_parameter( "flip", false ).unwrap_err().to_string();
+	assert!( e.contains( "Parameter flip is not true or false: 1" ), "{}", e );
+	let e = t.string_parameter( "name" ).unwrap_err().to_string();
+	assert!( e.contains( "Parameter name is not a string: 2" ), "{}", e );
+	let e = t.map_parameter( "options" ).unwrap_err().to_string();
+	assert!( e.contains( "Parameter options is not a map: [\"a\"]" ), "{}", e );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use asset::{Asset, AssetBuild, ToolRun, Watch};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn setup( name: &str ) -> ( String, AssetBuild, Vec<ToolRun> ) {
+	let path = common::project( name );
+	common::write( &path, "content/asset_config.yaml", "\
+tool: $asset
+command: copy
+" );
+	common::write( &path, "content/main.asset_config.yaml", "\
+output: a.txt
+input: a.txt
+---
+output: b.txt
+input: images/*.png
+---
+output: c.txt
+input: sounds
+" );
+	common::write( &path, "content/other.asset_config.yaml", "\
+output: d.txt
+input: d.txt
+" );
+	let asset_build = common::asset_build( &path );
+	let tool_runs = Asset::collect_tool_runs( &asset_build ).unwrap();
+	assert_eq!( tool_runs.len(), 4 );
+	( path, asset_build, tool_runs )
+}
+
+// the names the watcher would report for files below the project
+fn changed( path: &str, asset_build: &AssetBuild, names: &[&str] ) -> Vec<String> {
+	let content_directory = fs::canonicalize( &asset_build.content_directory ).unwrap();
+	let ignored = Watch::ignored_directories( asset_build, &content_directory );
+	let root = fs::canonicalize( path ).unwrap();
+	let paths = names.iter().map( |n| root.join( n ) ).collect::<Vec<PathBuf>>();
+	Watch::changed_names( asset_build, &content_directory, &ignored, &paths )
+}
+
+#[test]
+fn inputs_select_their_runs() {
+	let ( path, asset_build, tool_runs ) = setup( "inputs_select_their_runs" );
+	let selected = |names: &[&str]| Watch::selected( &tool_runs, &changed( &path, &asset_build, names ), false );
+
+	assert_eq!( selected( &[ "content/a.txt" ] ), vec![ 0 ] );
+	assert_eq!( selected( &[ "content/images/new.png" ] ), vec![ 1 ] );
+	assert!( selected( &[ "content/images/sub/new.png", "content/images/new.jpg" ] ).is_empty() );
+	assert_eq!( selected( &[ "content/sounds/sub/boom.wav" ] ), vec![ 2 ] );
+	assert_eq!( selected( &[ "content/sounds/a.wav", "content/d.txt", "content/sounds/a.wav" ] ), vec![ 2, 3 ] );
+	assert!( selected( &[ "content/unused.txt" ] ).is_empty() );
+}
+
+#[test]
+fn configs_select_their_runs() {
+	let ( path, asset_build, tool_runs ) = setup( "configs_select_their_runs" );
+	let selected = |names: &[&str]| Watch::selected( &tool_runs, &changed( &path, &asset_build, names ), false );
+
+	assert_eq!( selected( &[ "content/main.asset_config.yaml" ] ), vec![ 0, 1, 2 ] );
+	assert_eq!( selected( &[ "content/other.asset_config.yaml" ] ), vec![ 3 ] );
+	// the root config and includes can change everything
+	assert_eq!( selected( &[ "content/asset_config.yaml" ] ), vec![ 0, 1, 2, 3 ] );
+	assert_eq!( selected( &[ "content/templates/copy.yaml" ] ), vec![ 0, 1, 2, 3 ] );
+	assert_eq!( Watch::selected( &tool_runs, &[], true ), vec![ 0, 1, 2, 3 ] );
+}
+
+#[test]
+fn build_directories_are_ignored() {
+	let path = common::project( "build_directories_are_ignored" );
+	let asset_build = AssetBuild::new( &format!( "{}/content", path ), &format!( "{}/content/data", path ), &format!( "{}/content/temp", path ), "", "" );
+	assert_eq!(
+		changed( &path, &asset_build, &[ "content/data/a.txt", "content/temp/logs/a.log", "content/a.txt", "outside.txt" ] ),
+		vec![ format!( "{}/content/a.txt", path ) ]
+	);
+
+	// a data directory around the content can't be ignored
+	let asset_build = AssetBuild::new( &format!( "{}/content", path ), &path, &format!( "{}/temp", path ), "", "" );
+	let content_directory = fs::canonicalize( &asset_build.content_directory ).unwrap();
+	let ignored = Watch::ignored_directories( &asset_build, &content_directory );
+	assert_eq!( ignored, vec![ fs::canonicalize( format!( "{}/temp", path ) ).unwrap() ] );
+	assert!( Path::new( &format!( "{}/temp", path ) ).is_dir() );
+	assert_eq!( changed( &path, &asset_build, &[ "content/a.txt" ] ), vec![ format!( "{}/content/a.txt", path ) ] );
+}
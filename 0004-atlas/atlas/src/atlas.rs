@@ -1,6 +1,8 @@
-use crate::OmError;
+use omt_core::OmError;
+use packer::Vfs;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use image::{ DynamicImage, ImageFormat, GenericImage, GenericImageView };
+use log::{debug, error, warn};
 use regex::Regex;
 use std::fs::File;
 use std::io::{BufReader, Read, Write};
@@ -35,8 +37,8 @@ impl Entry {
 			image: 		None,
 			x:			0,
 			y:			0,
-			width: 		width,
-			height: 	height,
+			width,
+			height,
 		}
 	}
 
@@ -87,12 +89,12 @@ impl Entry {
 }
 
 fn simple_format_u32( f: &str, n: u32 ) -> String {
-	let s = f.clone();
+	let s = f;
 	let re = Regex::new(r"(%d)").unwrap();
 
 //	println!("simple_format_u32 {:?} with {:?}", s, re );
 	let s = re.replace_all(
-		&s,
+		s,
 		|c: &regex::Captures| {
 			let placeholder = c.get(1).map_or( "", |m| m.as_str() );
 //			println!("Found {:?}", placeholder );
@@ -100,7 +102,7 @@ fn simple_format_u32( f: &str, n: u32 ) -> String {
 				"" => "".to_string(),
 				"%d" => n.to_string(),
 				x => {
-					println!("simple_format_u32 got {:?}", x);
+					warn!("simple_format_u32 got {:?}", x);
 					x.to_string()
 				},
 			}
@@ -120,9 +122,9 @@ struct Row {
 impl Row {
 	fn new( y: u32, width: u32, height: u32 ) -> Row {
 		Row {
-			y: y,
-			width: width,
-			height: height,
+			y,
+			width,
+			height,
 			end_x: 0
 		}
 	}
@@ -171,8 +173,8 @@ impl std::fmt::Debug for Atlas {
 impl Atlas {
 	fn new( size: u32, border: u32 ) -> Atlas {
 		Atlas {
-			size: size,
-			border: border,
+			size,
+			border,
 			entries: Vec::new(),
 			image: Some( image::DynamicImage::new_rgba8(size, size) ),
 			rows: Vec::new(),
@@ -182,7 +184,7 @@ impl Atlas {
 
 	fn new_from_atlas( atlasname: &str, size: u32 ) -> Atlas {
 		let mut a = Atlas {
-			size: size,
+			size,
 			border: 0,
 			entries: Vec::new(),
 			image: None,
@@ -190,9 +192,8 @@ impl Atlas {
 			used_height:0 ,
 		};
 
-		match a.load_atlas( &atlasname, a.size ) {
-			Err( e ) => println!("{:?}", e ),
-			_ => {},
+		if let Err( e ) = a.load_atlas( atlasname, a.size ) {
+			error!("{:?}", e );
 		};
 
 		a
@@ -228,31 +229,30 @@ impl Atlas {
 	fn add_entry_to_row_with_index( &mut self, entry: &Entry, row_index: usize ) -> bool {
 		match self.rows.get_mut( row_index ) {
 			None => false,	// give up, should never happen
-			Some( row ) => {
+			Some( row ) if row.would_fit( entry.width, entry.height  ) => {
 //				println!("Got row {:?}", row );
-				if row.would_fit( entry.width, entry.height  ) {
-					// add it
-					let mut e = entry.clone();
-					// blitting
-					let x = row.end_x;
-					let y = row.y;
-					match &mut self.image {
-						None => {},
-						Some( di ) => {
-							Atlas::blit( di, &e.image.unwrap(), x, y );
-						},
-					}
-					row.end_x += e.width;
-					e.image = None;	// cleanup, data not needed anymore
-					e.set_position( x, y );
-					self.entries.push(
-						e
-					);
-					true
-				} else {
-//					println!("Row {:?} would not fit {:?}", row, entry );
-					false
+				// add it
+				let mut e = entry.clone();
+				// blitting
+				let x = row.end_x;
+				let y = row.y;
+				match &mut self.image {
+					None => {},
+					Some( di ) => {
+						Atlas::blit( di, &e.image.unwrap(), x, y );
+					},
 				}
+				row.end_x += e.width;
+				e.image = None;	// cleanup, data not needed anymore
+				e.set_position( x, y );
+				self.entries.push(
+					e
+				);
+				true
+			},
+			Some( _row ) => {
+//				println!("Row {:?} would not fit {:?}", row, entry );
+				false
 			}
 		}
 	}
@@ -279,7 +279,7 @@ impl Atlas {
 				}
 			}
 
-			if candidates.len() > 0 {
+			if !candidates.is_empty() {
 				// find best candidate
 				let best_candidate_index = 0;	// :TODO: actually find best candidate
 				/*
@@ -303,10 +303,12 @@ impl Atlas {
 	fn save_png( &self, filename: &str ) -> Result< u32, OmError > {
 //		Err( OmError::NotImplemented("Atlas::save_png".to_string()))
 		match self.image.as_ref().unwrap().save_with_format(filename, ImageFormat::PNG) {
-			_ => Ok( 0 )
+			Ok( _ ) => Ok( 0 ),
+			Err( e ) => Err( OmError::Generic( format!( "Error saving {}: {}", filename, e ) ) ),
 		}
 	}
 
+	#[allow(clippy::identity_op, clippy::erasing_op)]	// keep the matrix indices readable
 	fn load_atlas( &mut self, filename: &str, size: u32 ) -> Result< u32, OmError > {
 		let f = match File::open(filename) {
 			Ok( f ) => f,
@@ -317,12 +319,12 @@ impl Atlas {
 		let magic = match bufreader.read_u16::<LittleEndian>() { //.unwrap_or( 0xffff );
 			Ok( m ) => m,
 			x => {
-				println!("{:?}", x);
+				debug!("{:?}", x);
 				return Err(OmError::Generic("reading from buffer".to_string()))
 			},
 		};
 		if magic != 0x4f53 {
-			println!("Got magic {:?} from {:?}", magic, bufreader);
+			debug!("Got magic {:?} from {:?}", magic, bufreader);
 			return Err( OmError::Generic("Broken file magic".to_string() ) );
 		}
 		let v = bufreader.read_u16::<LittleEndian>().unwrap_or( 0 );
@@ -337,7 +339,7 @@ impl Atlas {
 			}
 		}
 		let flags = bufreader.read_u8().unwrap_or( 0 );
-		if flags != 'S' as u8 {
+		if flags != b'S' {
 			return Err( OmError::Generic( ":TODO: compression not implemented".to_string() ) );
 		}
 		let chunk_version = [ 0x01u8, 0x00, 0x00, 0x00 ];
@@ -349,7 +351,7 @@ impl Atlas {
 		}
 		let entry_count = bufreader.read_u16::<LittleEndian>( ).unwrap_or( 0 );
 
-		println!("Got {:?} entries", entry_count );
+		debug!("Got {:?} entries", entry_count );
 
 		for _ei in 0..entry_count {
 			let mut name_buffer = [0u8;128];
@@ -381,10 +383,10 @@ impl Atlas {
 		};
 		f.write_u16::<LittleEndian>( 0x4f53 ).unwrap();
 		f.write_u16::<LittleEndian>( 0x0001 ).unwrap();
-		let compress = 'S';
+		let compress = b'S';
 		f.write_all(&[
 			0x4f, 0x4d, 0x41, 0x54, 0x4c, 0x41, 0x53,	// OMATLAS
-			compress as u8,
+			compress,
 			0x01, 0x00, 0x00, 0x00,
 
 		]).unwrap();
@@ -428,6 +430,18 @@ impl Atlas {
 		Ok( 0 )
 	}
 
+	// all files written by combine, when it created n atlases
+	pub fn filenames( output: &str, n: u32 ) -> Vec<String> {
+		( 0..n ).flat_map( |i| {
+			let outname = simple_format_u32( output, i );
+			vec![
+				format!("{}.png", outname ),
+				format!("{}.atlas", outname ),
+				format!("{}.map", outname ),
+			]
+		}).collect()
+	}
+
 	pub fn hello() {
 		println!("Atlas::hello()");
 	}
@@ -440,17 +454,17 @@ impl Atlas {
 			let atlasname = format!("{}.atlas", inname );
 			let pngname = format!("{}.png", inname );
 			if !Path::new( &atlasname ).exists() {
-				println!("{:?} not found. Stopping", atlasname );
+				debug!("{:?} not found. Stopping", atlasname );
 				break;
 			}
 			if !Path::new( &pngname ).exists() {
-				println!("{:?} not found. Stopping", pngname );
+				debug!("{:?} not found. Stopping", pngname );
 				break;
 			}
 			// load image, to get the size
 			let img = image::open(&pngname).unwrap();
 			if img.dimensions().0 != img.dimensions().1 {
-				println!("Error: Non-square texture atlas found with dimensions {:?}", img.dimensions());
+				warn!("Non-square texture atlas found with dimensions {:?}", img.dimensions());
 			}
 
 			let size = img.dimensions().0;
@@ -481,16 +495,55 @@ impl Atlas {
 		// collect inputs
 		for i in input {
 //			println!("Analysing {:?}", i );
-			let img = image::open(i).unwrap();
+			let img = match image::open(i) {
+				Ok( img ) => img,
+				Err( e ) => return Err( OmError::Generic( format!( "Error loading {}: {}", i, e ) ) ),
+			};
 
 			let mut e = Entry::new( i, 0, 0 );
 			e.set_image( img );
 			entries.push(e);
 		}
 
+		Atlas::combine_entries( output, size, border, entries )
+	}
+
+	// same as combine, but the inputs are read from the mounted directories and archives
+	pub fn combine_with_vfs(
+		output: &str, size: u32, border: u32, input: &[&str], vfs: &Vfs
+	) -> Result<u32, OmError>{
+		let mut data = Vec::new();
+		for i in input {
+			data.push( ( i.to_string(), vfs.read( i )? ) );
+		}
+		Atlas::combine_data( output, size, border, &data )
+	}
+
+	// same as combine, for images that are already loaded, as ( name, file content )
+	pub fn combine_data(
+		output: &str, size: u32, border: u32, input: &[( String, Vec<u8> )]
+	) -> Result<u32, OmError>{
+		let mut entries = Vec::new();
+		for ( name, data ) in input {
+			let img = match image::load_from_memory( data ) {
+				Ok( img ) => img,
+				Err( e ) => return Err( OmError::Generic( format!( "Error loading {}: {}", name, e ) ) ),
+			};
+
+			let mut e = Entry::new( name, 0, 0 );
+			e.set_image( img );
+			entries.push(e);
+		}
+
+		Atlas::combine_entries( output, size, border, entries )
+	}
+
+	fn combine_entries(
+		output: &str, size: u32, border: u32, mut entries: Vec<Entry>
+	) -> Result<u32, OmError>{
 		// sort entries by size
-		entries.sort_by( |a, b|
-			b.height.cmp( &a.height )	// higher ones first
+		entries.sort_by_key( |e|
+			std::cmp::Reverse( e.height )	// higher ones first
 		);
 
 
@@ -508,7 +561,7 @@ impl Atlas {
 			if !did_fit {
 				let mut a = Atlas::new( size, border );
 				if !a.add_entry( &e ) {
-					println!("‼️ Image doesn't fit into empty atlas {:?}", e );
+					error!("‼️ Image doesn't fit into empty atlas {:?}", e );
 					return Err(OmError::Generic("‼️ Image doesn't fit into empty atlas".to_string()));
 				}
 				atlases.push( a );				
@@ -0,0 +1,146 @@
+use clap::{Arg, App, ArgMatches, SubCommand};
+use log::{debug, error, info};
+use omt_core::OmError;
+use packer::Vfs;
+
+use crate::Atlas;
+
+pub struct Cli {
+}
+
+impl Cli {
+	pub fn app<'a, 'b>( name: &str ) -> App<'a, 'b> {
+// omt-atlas combine --output test-atlas-%d --size 2048 --border 0 --input ../Content/test.png
+		App::new( name )
+			.about("Handles atlases")
+			.subcommand(SubCommand::with_name("combine")
+				.arg(Arg::with_name("output")
+					.long("output")
+					.value_name("OUTPUT")
+					.help("Set the output")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("size")
+					.long("size")
+					.value_name("SIZE")
+					.help("Set the size")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("border")
+					.long("border")
+					.value_name("BORDER")
+					.help("Set the border")
+					.takes_value(true)
+				)
+				.arg(Arg::with_name("input")
+					.long("input")
+					.value_name("INPUT")
+					.help("Set the input")
+					.takes_value(true)
+					.multiple(true)
+				)
+				.arg(Arg::with_name("mount")
+					.long("mount")
+					.value_name("MOUNT")
+					.help("Read inputs from a directory or archive, can be given multiple times")
+					.takes_value(true)
+					.multiple(true)
+					.number_of_values(1)
+				)
+			)
+			.subcommand(SubCommand::with_name("info")
+				.arg(Arg::with_name("input")
+					.long("input")
+					.value_name("INPUT")
+					.help("Set the input")
+					.takes_value(true)
+				)
+			)
+	}
+
+	// returns the exit code
+	pub fn run( matches: &ArgMatches ) -> i32 {
+
+		if let ("combine", Some( sub_matches ) ) = matches.subcommand() {
+			let output = sub_matches.value_of("output").unwrap_or("output-atlas-%d").to_string();
+			let size   = sub_matches.value_of("size").unwrap_or("2048").to_string();
+			let border = sub_matches.value_of("border").unwrap_or("0").to_string();
+			let input  = sub_matches.values_of("input").unwrap().collect::<Vec<_>>(); 
+
+			let size = match size.parse::<u32>() {
+				Ok( n ) => n,
+				x => {
+					error!("Error parsing size {:?} >{}<", x, size );
+					return -1;
+				}
+			};
+
+			let border = match border.parse::<u32>() {
+				Ok( n ) => n,
+				x => {
+					error!("Error parsing border {:?} >{}<", x, border );
+					return -1;
+				}
+			};
+
+			debug!("output         : {:?}", output );
+			debug!("size           : {:?}", size );
+			debug!("border         : {:?}", border );
+	//		println!("input          : {:?}", input );
+			debug!("input          : [" );
+			for i in &input {
+				debug!("\t{:?}", i );
+			}
+			debug!("]" );
+
+			let mounts = sub_matches.values_of("mount").map( |m| m.collect::<Vec<_>>() ).unwrap_or_default();
+			let result = if mounts.is_empty() {
+				Atlas::combine( &output, size, border, &input )
+			} else {
+				let mut vfs = Vfs::new();
+				for m in &mounts {
+					debug!("mount          : {:?}", m );
+					if let Err( e ) = vfs.mount( m ) {
+						error!("Error mounting {:?}: {}", m, e );
+						return -1;
+					}
+				}
+				Atlas::combine_with_vfs( &output, size, border, &input, &vfs )
+			};
+
+			match result {
+				Ok( 1 ) => {
+					info!("1 atlas created" );
+					return 0;
+				},
+				Ok( n ) => {
+					info!("{:?} atlases created", n );
+					return 0;
+				},
+				Err( e ) => {
+					error!("Error combining atlas >{:?}>", e );
+					return -1;
+				}
+			}
+
+		}
+		if let ("info", Some( sub_matches ) ) = matches.subcommand() {
+			let input = sub_matches.value_of("input").unwrap_or("input-atlas-%d").to_string();
+			debug!("input         : {:?}", input );
+			match Atlas::info( &input ) {
+				Ok( _ ) => {
+					return 0;
+				},
+				Err( e ) => {
+					error!("Error getting info from  atlas." );
+					match e {
+						OmError::NotImplemented( e ) => error!("NotImplemented: {:?}", e ),
+						OmError::Generic( e ) => error!("Generic: {:?}", e ),
+					};
+					return -1;
+				}
+			}
+		}
+		-1
+	}
+}
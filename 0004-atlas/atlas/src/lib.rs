@@ -1,5 +1,7 @@
 pub mod atlas;
 pub use atlas::Atlas;
 
-pub mod omerror;
-pub use omerror::OmError;
+pub mod cli;
+pub use cli::Cli;
+
+pub use omt_core::OmError;
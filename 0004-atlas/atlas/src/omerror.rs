@@ -1,6 +0,0 @@
-
-#[derive(Debug)]
-pub enum OmError {
-	NotImplemented(String),
-	Generic(String),
-}
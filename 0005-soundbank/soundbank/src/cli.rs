@@ -0,0 +1,17 @@
+use clap::{App, ArgMatches};
+
+pub struct Cli {
+}
+
+impl Cli {
+	pub fn app<'a, 'b>( name: &str ) -> App<'a, 'b> {
+		App::new( name )
+			.about("Handles soundbanks")
+	}
+
+	// returns the exit code
+	pub fn run( _matches: &ArgMatches ) -> i32 {
+		println!("Hello, world!");
+		0
+	}
+}
@@ -1,3 +1,16 @@
+use std::process;
+
+use omt_core::Logging;
+use soundbank::Cli;
+
 fn main() {
-    println!("Hello, world!");
+	let matches = Cli::app( "omt-soundbank" )
+					.version("0.1")
+					.author("Andreas N. <andreas@omni-mad.com>")
+					.args(&Logging::args())
+					.get_matches();
+
+	Logging::init( Logging::verbosity( &matches ) );
+
+	process::exit( Cli::run( &matches ) );
 }
@@ -0,0 +1,26 @@
+use crc::crc32;
+
+// the name rule shared by packer and runtime, archives only store the crc of the cleaned up name
+pub struct CrcName {
+}
+
+impl CrcName {
+	pub fn clean( filename: &str ) -> String {
+		let downcase_name = filename.to_lowercase();
+		// Ruby: .gsub( /\W\./, ' ' ) // should be 'a-zA-Z0-9_', but actual code behaves differently
+		downcase_name.chars().map(|c| match c {
+			'0'..='9' => c,
+			'a'..='z' => c,
+	//			'A'..='Z' => c,	// already downcase
+			'!'..='@' => c,
+			'['..='`' => c,
+			'{'..='~' => c,
+	//		0x7f => c,			// ignore DEL
+			_ => ' '
+		}).collect()
+	}
+
+	pub fn crc( filename: &str ) -> u32 {
+		crc32::checksum_ieee( CrcName::clean( filename ).as_bytes() )
+	}
+}
@@ -0,0 +1,11 @@
+pub mod crc_name;
+pub use crc_name::CrcName;
+
+pub mod logging;
+pub use logging::Logging;
+
+pub mod omerror;
+pub use omerror::OmError;
+
+pub mod paths;
+pub use paths::Paths;
@@ -0,0 +1,68 @@
+use clap::{Arg, ArgMatches};
+use log::{Level, LevelFilter};
+
+use std::env;
+use std::io::Write;
+
+// e.g. OMT_LOG=debug or OMT_LOG=asset=trace, only used when neither -q nor -v is given
+pub const LOG_ENV: &str = "OMT_LOG";
+
+pub struct Logging {
+}
+
+impl Logging {
+	pub fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+		vec![
+			Arg::with_name("quiet")
+				.long("quiet")
+				.short("q")
+				.help("Only print errors")
+				.global(true)
+				.conflicts_with("verbose"),
+			Arg::with_name("verbose")
+				.long("verbose")
+				.short("v")
+				.help("Print more diagnostics, -vv for even more")
+				.multiple(true)
+				.global(true),
+		]
+	}
+
+	// -1 for -q, 0 by default, 1 for -v, 2 for -vv
+	pub fn verbosity( matches: &ArgMatches ) -> i32 {
+		// global args can be given before or after the subcommand
+		let mut quiet = false;
+		let mut verbosity = 0;
+		let mut m = Some( matches );
+		while let Some( matches ) = m {
+			quiet |= matches.is_present( "quiet" );
+			verbosity = verbosity.max( matches.occurrences_of( "verbose" ) as i32 );
+			m = matches.subcommand().1;
+		}
+		if quiet { -1 } else { verbosity }
+	}
+
+	// all diagnostics go to stderr, stdout is kept for actual output
+	pub fn init( verbosity: i32 ) {
+		let mut builder = env_logger::Builder::new();
+		builder.target( env_logger::Target::Stderr );
+		builder.format( |buf, record| match record.level() {
+			Level::Info => writeln!( buf, "{}", record.args() ),
+			level => writeln!( buf, "{}: {}", level.to_string().to_lowercase(), record.args() ),
+		});
+		match ( verbosity, env::var( LOG_ENV ) ) {
+			( 0, Ok( filters ) ) => builder.parse_filters( &filters ),
+			( v, _ ) => builder.filter_level( Logging::level_filter( v ) ),
+		};
+		let _ = builder.try_init();
+	}
+
+	fn level_filter( verbosity: i32 ) -> LevelFilter {
+		match verbosity {
+			v if v < 0	=> LevelFilter::Error,
+			0			=> LevelFilter::Info,
+			1			=> LevelFilter::Debug,
+			_			=> LevelFilter::Trace,
+		}
+	}
+}
@@ -0,0 +1,19 @@
+use std::fmt;
+
+#[derive(Debug,PartialEq)]
+pub enum OmError {
+	NotImplemented(String),
+	Generic(String),
+}
+
+impl fmt::Display for OmError {
+	fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+		match self {
+			OmError::NotImplemented( e ) => write!(f, "NotImplemented: {}", e),
+			OmError::Generic( e ) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl std::error::Error for OmError {
+}
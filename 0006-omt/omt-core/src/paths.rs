@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::Path;
+
+pub struct Paths {
+}
+
+impl Paths {
+	// basepath relative names are always joined with '/', like the paklists use them
+	// an empty basepath is the current directory, like the parent of a plain file name
+	pub fn join( basepath: &str, name: &str ) -> String {
+		if basepath.is_empty() {
+			name.to_string()
+		} else {
+			format!( "{}/{}", basepath, name )
+		}
+	}
+
+	// name of path relative to basepath, None if it is outside of basepath
+	pub fn relative( path: &Path, basepath: &Path ) -> Option<String> {
+		path.strip_prefix( basepath ).ok().map( |p| {
+			p.components().map( |c| c.as_os_str().to_string_lossy() ).collect::<Vec<_>>().join( "/" )
+		})
+	}
+
+	// removes "." and resolves ".." without touching the file system, so paths written differently compare equal
+	pub fn normalize( path: &str ) -> String {
+		let absolute = path.starts_with( '/' );
+		let mut parts: Vec<&str> = Vec::new();
+		for p in path.split( '/' ) {
+			match p {
+				"" | "." => {},
+				".." => match parts.last() {
+					Some( l ) if *l != ".." => { parts.pop(); },
+					_ if absolute => {},	// there is nothing above the root
+					_ => parts.push( p ),
+				},
+				p => parts.push( p ),
+			}
+		}
+		match ( absolute, parts.is_empty() ) {
+			( true, _ ) => format!( "/{}", parts.join( "/" ) ),
+			( false, true ) => ".".to_string(),
+			( false, false ) => parts.join( "/" ),
+		}
+	}
+
+	// all files below directory, as sorted names relative to it
+	pub fn files_in_directory( directory: &Path ) -> Vec<String> {
+		let mut files = Vec::new();
+		let mut pending = vec![ directory.to_path_buf() ];
+		while let Some( dir ) = pending.pop() {
+			let entries = match fs::read_dir( &dir ) {
+				Ok( entries ) => entries,
+				Err( _ ) => continue,
+			};
+			for entry in entries.flatten() {
+				let path = entry.path();
+				if path.is_dir() {
+					pending.push( path );
+				} else if let Some( name ) = Paths::relative( &path, directory ) {
+					files.push( name );
+				}
+			}
+		}
+		files.sort();
+		files
+	}
+}
@@ -0,0 +1,33 @@
+use clap::{App, AppSettings};
+use omt_core::Logging;
+
+use std::process;
+
+// omt packer pack ...
+// omt asset build ...
+// omt atlas combine ...
+fn main() {
+	let matches = App::new("omt")
+					.version("0.1")
+					.author("Andreas N. <andreas@omni-mad.com>")
+					.about("Omni-Mad Tools")
+					.setting(AppSettings::SubcommandRequiredElseHelp)
+					.args(&Logging::args())
+					.subcommand( packer::Cli::app( "packer" ) )
+					.subcommand( asset::Cli::app( "asset" ) )
+					.subcommand( atlas::Cli::app( "atlas" ) )
+					.subcommand( soundbank::Cli::app( "soundbank" ) )
+					.get_matches();
+
+	Logging::init( Logging::verbosity( &matches ) );
+
+	let exit_code = match matches.subcommand() {
+		( "packer", Some( sub_matches ) )		=> packer::Cli::run( sub_matches ),
+		( "asset", Some( sub_matches ) )		=> asset::Cli::run( sub_matches ),
+		( "atlas", Some( sub_matches ) )		=> atlas::Cli::run( sub_matches ),
+		( "soundbank", Some( sub_matches ) )	=> soundbank::Cli::run( sub_matches ),
+		_ => -1,
+	};
+
+	process::exit( exit_code );
+}